@@ -42,7 +42,7 @@ async fn main(_spawner: Spawner) {
             Input::new(p.PIN_11, Pull::Down),
         ];
 
-        Col2Row::debounced(cols, rows)
+        Col2Row::new(cols, rows).idle().debounced()
     };
 
     let map = {
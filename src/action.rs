@@ -1,12 +1,31 @@
-use crate::interface::usb::KeyCode;
+use crate::{
+    gamepad::{Axis, AxisDirection},
+    interface::usb::{KeyCode, Modifiers},
+};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Action {
     Code(KeyCode),
+    Modded {
+        mods: Modifiers,
+        code: KeyCode,
+    },
     // ModTap(Modifier, KeyCode),
     // LayerTap(Layer, KeyCode),
-    TapHold { tap: KeyCode, hold: KeyCode },
+    TapHold {
+        tap: KeyCode,
+        hold: KeyCode,
+    },
     MomentaryLayer(u8),
     ToggleLayer(u8),
+    CapsWord,
+    AutoShift(KeyCode),
+    Repeat,
+    AltRepeat,
+    /// Deflects a gamepad axis by how far the key is pressed.
+    Axis {
+        axis: Axis,
+        direction: AxisDirection,
+    },
 }
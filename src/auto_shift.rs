@@ -0,0 +1,122 @@
+use embassy_time::Duration;
+
+use crate::interface::usb::KeyCode;
+
+pub const DEFAULT_AUTO_SHIFT_TIMEOUT: Duration = Duration::from_millis(175);
+
+/// Sends the shifted variant of a key when it is held past a timeout.
+///
+/// Keys can also be auto-shifted individually with
+/// [`Action::AutoShift`](crate::prelude::Action::AutoShift), in which case only the timeout of
+/// this configuration applies.
+#[derive(Clone, Copy, Debug)]
+pub struct AutoShiftConfig {
+    /// Hold duration after which the shifted variant is sent. Default: 175 ms.
+    timeout: Duration,
+    /// Whether letters are auto-shifted. Default: true.
+    alpha: bool,
+    /// Whether digits are auto-shifted. Default: true.
+    numeric: bool,
+    /// Whether symbols such as `-`, `[` and `/` are auto-shifted. Default: true.
+    symbols: bool,
+    /// Keys that are never auto-shifted. Default: none.
+    exclude: &'static [KeyCode],
+}
+
+impl AutoShiftConfig {
+    pub const fn new() -> Self {
+        Self {
+            timeout: DEFAULT_AUTO_SHIFT_TIMEOUT,
+            alpha: true,
+            numeric: true,
+            symbols: true,
+            exclude: &[],
+        }
+    }
+
+    /// Configuration where no keys are auto-shifted unless they use
+    /// [`Action::AutoShift`](crate::prelude::Action::AutoShift).
+    pub const fn none() -> Self {
+        Self::new().alpha(false).numeric(false).symbols(false)
+    }
+
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub const fn alpha(mut self, enabled: bool) -> Self {
+        self.alpha = enabled;
+        self
+    }
+
+    pub const fn numeric(mut self, enabled: bool) -> Self {
+        self.numeric = enabled;
+        self
+    }
+
+    pub const fn symbols(mut self, enabled: bool) -> Self {
+        self.symbols = enabled;
+        self
+    }
+
+    pub const fn exclude(mut self, codes: &'static [KeyCode]) -> Self {
+        self.exclude = codes;
+        self
+    }
+
+    pub(crate) fn get_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Whether `code` should be auto-shifted.
+    pub(crate) fn applies(&self, code: KeyCode) -> bool {
+        let enabled = (self.alpha && code.is_letter())
+            || (self.numeric && code.is_digit())
+            || (self.symbols && code.is_symbol());
+
+        enabled && !self.exclude.contains(&code)
+    }
+}
+
+impl Default for AutoShiftConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_to_enabled_categories() {
+        let config = AutoShiftConfig::new().numeric(false);
+        assert!(config.applies(KeyCode::KeyboardA));
+        assert!(config.applies(KeyCode::Slash));
+        assert!(!config.applies(KeyCode::Keyboard1));
+    }
+
+    #[test]
+    fn does_not_apply_to_modifiers_or_whitespace() {
+        let config = AutoShiftConfig::new();
+        assert!(!config.applies(KeyCode::LeftShift));
+        assert!(!config.applies(KeyCode::Space));
+        assert!(!config.applies(KeyCode::Enter));
+    }
+
+    #[test]
+    fn does_not_apply_to_excluded() {
+        let config = AutoShiftConfig::new().exclude(&[KeyCode::KeyboardQ]);
+        assert!(!config.applies(KeyCode::KeyboardQ));
+        assert!(config.applies(KeyCode::KeyboardW));
+    }
+
+    #[test]
+    fn none_applies_to_nothing() {
+        let config = AutoShiftConfig::none();
+        assert!(!config.applies(KeyCode::KeyboardA));
+        assert!(!config.applies(KeyCode::Keyboard1));
+        assert!(!config.applies(KeyCode::Minus));
+    }
+}
@@ -0,0 +1,193 @@
+use embassy_time::{Duration, Instant};
+
+use crate::{
+    debug,
+    interface::usb::{KeyCode, Modifiers},
+};
+
+pub const DEFAULT_CAPS_WORD_IDLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+const DEFAULT_SHIFTED: &[KeyCode] = &[KeyCode::Minus];
+
+const DEFAULT_CONTINUING: &[KeyCode] = &[
+    KeyCode::Keyboard1,
+    KeyCode::Keyboard2,
+    KeyCode::Keyboard3,
+    KeyCode::Keyboard4,
+    KeyCode::Keyboard5,
+    KeyCode::Keyboard6,
+    KeyCode::Keyboard7,
+    KeyCode::Keyboard8,
+    KeyCode::Keyboard9,
+    KeyCode::Keyboard0,
+    KeyCode::Backspace,
+    KeyCode::Delete,
+];
+
+#[derive(Clone, Copy, Debug)]
+pub struct CapsWordConfig {
+    /// Time without any key presses after which caps word turns itself off. Default: 5 s.
+    idle_timeout: Duration,
+    /// Keys, in addition to letters, that are shifted and continue the word. Default: `-`.
+    shifted: &'static [KeyCode],
+    /// Keys that continue the word without being shifted. Default: digits, backspace and delete.
+    continuing: &'static [KeyCode],
+}
+
+impl CapsWordConfig {
+    pub const fn new() -> Self {
+        Self {
+            idle_timeout: DEFAULT_CAPS_WORD_IDLE_TIMEOUT,
+            shifted: DEFAULT_SHIFTED,
+            continuing: DEFAULT_CONTINUING,
+        }
+    }
+
+    pub const fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    pub const fn shifted(mut self, codes: &'static [KeyCode]) -> Self {
+        self.shifted = codes;
+        self
+    }
+
+    pub const fn continuing(mut self, codes: &'static [KeyCode]) -> Self {
+        self.continuing = codes;
+        self
+    }
+
+    fn classify(&self, code: KeyCode) -> Class {
+        if code.is_letter() || self.shifted.contains(&code) {
+            Class::Shifted
+        } else if self.continuing.contains(&code)
+            || matches!(code, KeyCode::LeftShift | KeyCode::RightShift)
+        {
+            Class::Continuing
+        } else {
+            Class::Breaking
+        }
+    }
+}
+
+impl Default for CapsWordConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Class {
+    Shifted,
+    Continuing,
+    Breaking,
+}
+
+/// Engine-level filter that shifts letters until a word-breaking key is pressed.
+pub(crate) struct CapsWord {
+    config: CapsWordConfig,
+    last_activity: Option<Instant>,
+}
+
+impl CapsWord {
+    pub const fn new(config: CapsWordConfig) -> Self {
+        Self {
+            config,
+            last_activity: None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.last_activity
+            .is_some_and(|x| x.elapsed() < self.config.idle_timeout)
+    }
+
+    pub fn toggle(&mut self) {
+        if self.is_active() {
+            self.deactivate()
+        } else {
+            self.activate()
+        }
+    }
+
+    pub fn activate(&mut self) {
+        debug!("Caps word activated");
+        self.last_activity = Some(Instant::now());
+    }
+
+    pub fn deactivate(&mut self) {
+        if self.last_activity.take().is_some() {
+            debug!("Caps word deactivated");
+        }
+    }
+
+    /// Feeds a pressed key code through caps word, returning the modifiers that should be
+    /// applied while it is held.
+    pub fn process(&mut self, code: KeyCode) -> Modifiers {
+        if !self.is_active() {
+            self.deactivate();
+            return Modifiers::empty();
+        }
+
+        match self.config.classify(code) {
+            Class::Shifted => {
+                self.last_activity = Some(Instant::now());
+                Modifiers::LEFT_SHIFT
+            }
+            Class::Continuing => {
+                self.last_activity = Some(Instant::now());
+                Modifiers::empty()
+            }
+            Class::Breaking => {
+                self.deactivate();
+                Modifiers::empty()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letters_are_shifted() {
+        let config = CapsWordConfig::new();
+        assert_eq!(config.classify(KeyCode::KeyboardA), Class::Shifted);
+        assert_eq!(config.classify(KeyCode::KeyboardZ), Class::Shifted);
+    }
+
+    #[test]
+    fn minus_is_shifted_by_default() {
+        assert_eq!(
+            CapsWordConfig::new().classify(KeyCode::Minus),
+            Class::Shifted
+        );
+    }
+
+    #[test]
+    fn digits_and_shift_continue() {
+        let config = CapsWordConfig::new();
+        assert_eq!(config.classify(KeyCode::Keyboard1), Class::Continuing);
+        assert_eq!(config.classify(KeyCode::Backspace), Class::Continuing);
+        assert_eq!(config.classify(KeyCode::LeftShift), Class::Continuing);
+    }
+
+    #[test]
+    fn space_punctuation_and_other_modifiers_break() {
+        let config = CapsWordConfig::new();
+        assert_eq!(config.classify(KeyCode::Space), Class::Breaking);
+        assert_eq!(config.classify(KeyCode::Dot), Class::Breaking);
+        assert_eq!(config.classify(KeyCode::LeftControl), Class::Breaking);
+    }
+
+    #[test]
+    fn configured_keys_override_defaults() {
+        let config = CapsWordConfig::new()
+            .shifted(&[])
+            .continuing(&[KeyCode::Minus]);
+        assert_eq!(config.classify(KeyCode::Minus), Class::Continuing);
+        assert_eq!(config.classify(KeyCode::Keyboard1), Class::Breaking);
+    }
+}
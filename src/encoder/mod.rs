@@ -0,0 +1,73 @@
+mod quadrature;
+
+use core::convert::Infallible;
+
+use crate::{Action, map::EncoderMap};
+
+pub use quadrature::Quadrature;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Direction {
+    Clockwise,
+    CounterClockwise,
+}
+
+pub trait Encode {
+    type Error: core::fmt::Debug;
+
+    /// Samples the encoder, returning the direction of a completed detent, if any.
+    fn poll(&mut self) -> Result<Option<Direction>, Self::Error>;
+}
+
+/// A set of encoders together with the actions they are bound to.
+pub trait Encoders {
+    type Error: core::fmt::Debug;
+
+    fn count(&self) -> usize;
+    fn poll(&mut self, index: usize) -> Result<Option<Direction>, Self::Error>;
+    fn get(&self, index: usize, direction: Direction, active_layers: u32) -> Option<Action>;
+}
+
+impl Encoders for () {
+    type Error = Infallible;
+
+    fn count(&self) -> usize {
+        0
+    }
+
+    fn poll(&mut self, _index: usize) -> Result<Option<Direction>, Infallible> {
+        Ok(None)
+    }
+
+    fn get(&self, _index: usize, _direction: Direction, _active_layers: u32) -> Option<Action> {
+        None
+    }
+}
+
+pub struct MappedEncoders<E, const N: usize, const D: usize> {
+    encoders: [E; N],
+    map: EncoderMap<N, D>,
+}
+
+impl<E, const N: usize, const D: usize> MappedEncoders<E, N, D> {
+    pub const fn new(encoders: [E; N], map: EncoderMap<N, D>) -> Self {
+        Self { encoders, map }
+    }
+}
+
+impl<E: Encode, const N: usize, const D: usize> Encoders for MappedEncoders<E, N, D> {
+    type Error = E::Error;
+
+    fn count(&self) -> usize {
+        N
+    }
+
+    fn poll(&mut self, index: usize) -> Result<Option<Direction>, E::Error> {
+        self.encoders[index].poll()
+    }
+
+    fn get(&self, index: usize, direction: Direction, active_layers: u32) -> Option<Action> {
+        self.map.get(index as u8, direction, active_layers)
+    }
+}
@@ -0,0 +1,180 @@
+use embedded_hal::digital::InputPin;
+
+use crate::scan::ScanError;
+
+use super::{Direction, Encode};
+
+/// Quadrature steps per detent of most common encoders.
+pub const DEFAULT_RESOLUTION: u8 = 4;
+
+/// Consecutive identical samples needed before a change of pin state is accepted.
+pub const DEFAULT_DEBOUNCE: u8 = 2;
+
+// Indexed by `(previous << 2) | current`, where a state is `(a << 1) | b`. Transitions where both
+// pins change at once are invalid and ignored.
+const TRANSITIONS: [i8; 16] = [0, -1, 1, 0, 1, 0, 0, -1, -1, 0, 0, 1, 0, 1, -1, 0];
+
+pub struct Quadrature<A, B> {
+    a: A,
+    b: B,
+    decoder: Decoder,
+}
+
+impl<A, B> Quadrature<A, B>
+where
+    A: InputPin,
+    B: InputPin,
+{
+    pub const fn new(a: A, b: B) -> Self {
+        Self {
+            a,
+            b,
+            decoder: Decoder::new(DEFAULT_RESOLUTION, DEFAULT_DEBOUNCE),
+        }
+    }
+
+    /// Sets the number of quadrature steps per detent.
+    pub const fn resolution(mut self, steps: u8) -> Self {
+        self.decoder.resolution = steps;
+        self
+    }
+
+    /// Sets the number of consecutive identical samples needed to accept a change of pin state.
+    pub const fn debounce(mut self, samples: u8) -> Self {
+        self.decoder.debounce = samples;
+        self
+    }
+}
+
+impl<A, B> Encode for Quadrature<A, B>
+where
+    A: InputPin,
+    B: InputPin,
+{
+    type Error = ScanError;
+
+    fn poll(&mut self) -> Result<Option<Direction>, ScanError> {
+        let a = self.a.is_high().map_err(ScanError::pin)?;
+        let b = self.b.is_high().map_err(ScanError::pin)?;
+        Ok(self.decoder.update(((a as u8) << 1) | b as u8))
+    }
+}
+
+struct Decoder {
+    resolution: u8,
+    debounce: u8,
+    state: Option<u8>,
+    candidate: u8,
+    stable: u8,
+    steps: i8,
+}
+
+impl Decoder {
+    const fn new(resolution: u8, debounce: u8) -> Self {
+        Self {
+            resolution,
+            debounce,
+            state: None,
+            candidate: 0,
+            stable: 0,
+            steps: 0,
+        }
+    }
+
+    fn update(&mut self, sample: u8) -> Option<Direction> {
+        let Some(state) = self.state else {
+            self.state = Some(sample);
+            return None;
+        };
+
+        if sample != self.candidate {
+            self.candidate = sample;
+            self.stable = 0;
+        }
+
+        self.stable = self.stable.saturating_add(1);
+
+        if self.candidate == state || self.stable < self.debounce {
+            return None;
+        }
+
+        self.state = Some(self.candidate);
+        self.steps += TRANSITIONS[((state << 2) | self.candidate) as usize];
+
+        if self.steps >= self.resolution as i8 {
+            self.steps = 0;
+            Some(Direction::Clockwise)
+        } else if self.steps <= -(self.resolution as i8) {
+            self.steps = 0;
+            Some(Direction::CounterClockwise)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CW: [u8; 4] = [0b10, 0b11, 0b01, 0b00];
+    const CCW: [u8; 4] = [0b01, 0b11, 0b10, 0b00];
+
+    fn feed(decoder: &mut Decoder, samples: &[u8], repeat: usize) -> Option<Direction> {
+        let mut result = None;
+        for sample in samples {
+            for _ in 0..repeat {
+                result = result.or(decoder.update(*sample));
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn full_detent_clockwise() {
+        let mut decoder = Decoder::new(4, 1);
+        decoder.update(0b00);
+        assert_eq!(feed(&mut decoder, &CW, 1), Some(Direction::Clockwise));
+    }
+
+    #[test]
+    fn full_detent_counter_clockwise() {
+        let mut decoder = Decoder::new(4, 1);
+        decoder.update(0b00);
+        assert_eq!(
+            feed(&mut decoder, &CCW, 1),
+            Some(Direction::CounterClockwise)
+        );
+    }
+
+    #[test]
+    fn partial_detent_is_ignored() {
+        let mut decoder = Decoder::new(4, 1);
+        decoder.update(0b00);
+        assert_eq!(feed(&mut decoder, &CW[..3], 1), None);
+    }
+
+    #[test]
+    fn resolution_of_two() {
+        let mut decoder = Decoder::new(2, 1);
+        decoder.update(0b00);
+        assert_eq!(feed(&mut decoder, &CW[..2], 1), Some(Direction::Clockwise));
+    }
+
+    #[test]
+    fn single_sample_bounces_are_filtered() {
+        let mut decoder = Decoder::new(4, 2);
+        decoder.update(0b00);
+        assert_eq!(feed(&mut decoder, &[0b01, 0b00], 1), None);
+        assert_eq!(decoder.steps, 0);
+        assert_eq!(feed(&mut decoder, &CW, 2), Some(Direction::Clockwise));
+    }
+
+    #[test]
+    fn back_and_forth_cancels() {
+        let mut decoder = Decoder::new(4, 1);
+        decoder.update(0b00);
+        assert_eq!(feed(&mut decoder, &[0b01, 0b00, 0b01, 0b00], 1), None);
+        assert_eq!(decoder.steps, 0);
+    }
+}
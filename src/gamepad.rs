@@ -0,0 +1,117 @@
+use crate::scan::FULL_TRAVEL;
+
+/// Largest deflection of a gamepad axis in either direction.
+pub const MAX_DEFLECTION: i8 = i8::MAX;
+
+/// Axis of the gamepad sticks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Axis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+}
+
+impl Axis {
+    pub const ALL: [Axis; 4] = [Axis::LeftX, Axis::LeftY, Axis::RightX, Axis::RightY];
+}
+
+/// Direction a key deflects an axis in. Negative is left on X axes and up on Y axes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AxisDirection {
+    Negative,
+    Positive,
+}
+
+/// How the travel of a key past the dead zone maps to the deflection of an axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Curve {
+    Linear,
+    /// Finer control near the center.
+    Quadratic,
+    /// Even finer control near the center.
+    Cubic,
+}
+
+/// Configuration of [`Action::Axis`](crate::prelude::Action::Axis). Keys read by digital
+/// scanners deflect their axis fully while pressed.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GamepadConfig {
+    /// Travel below which a key leaves its axis centered. Default: 1/8 of
+    /// [`FULL_TRAVEL`](crate::prelude::FULL_TRAVEL).
+    dead_zone: u8,
+    /// Default: [`Curve::Linear`].
+    curve: Curve,
+}
+
+impl GamepadConfig {
+    pub const fn new() -> Self {
+        Self {
+            dead_zone: FULL_TRAVEL / 8,
+            curve: Curve::Linear,
+        }
+    }
+
+    pub const fn dead_zone(mut self, travel: u8) -> Self {
+        self.dead_zone = travel;
+        self
+    }
+
+    pub const fn curve(mut self, curve: Curve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    /// Deflection of an axis by a key at `travel`, from 0 to [`MAX_DEFLECTION`].
+    pub(crate) fn deflection(&self, travel: u8) -> i8 {
+        if travel <= self.dead_zone {
+            return 0;
+        }
+
+        let travel = (travel - self.dead_zone) as u32;
+        let range = (FULL_TRAVEL - self.dead_zone) as u32;
+        let (travel, range) = match self.curve {
+            Curve::Linear => (travel, range),
+            Curve::Quadratic => (travel.pow(2), range.pow(2)),
+            Curve::Cubic => (travel.pow(3), range.pow(3)),
+        };
+
+        (travel * MAX_DEFLECTION as u32 / range) as i8
+    }
+}
+
+impl Default for GamepadConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dead_zone_centers_axis() {
+        let config = GamepadConfig::new().dead_zone(55);
+        assert_eq!(config.deflection(0), 0);
+        assert_eq!(config.deflection(55), 0);
+        assert_eq!(config.deflection(155), 63);
+        assert_eq!(config.deflection(FULL_TRAVEL), MAX_DEFLECTION);
+    }
+
+    #[test]
+    fn curves_keep_full_deflection() {
+        let config = GamepadConfig::new().dead_zone(0);
+        let half = [Curve::Linear, Curve::Quadratic, Curve::Cubic]
+            .map(|curve| config.curve(curve).deflection(128));
+        assert_eq!(half, [63, 31, 16]);
+
+        let full = [Curve::Quadratic, Curve::Cubic]
+            .map(|curve| config.curve(curve).deflection(FULL_TRAVEL));
+        assert_eq!(full, [MAX_DEFLECTION; 2]);
+    }
+}
@@ -0,0 +1,116 @@
+//! Handler mock for testing the keyboard on the host.
+
+extern crate std;
+
+use std::vec::Vec;
+
+use super::{
+    Handler,
+    usb::{KeyCode, Modifiers},
+};
+
+/// Report as the host receives it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MockReport {
+    pub mods: Modifiers,
+    pub codes: Vec<KeyCode>,
+}
+
+impl MockReport {
+    pub fn new(mods: Modifiers, codes: &[KeyCode]) -> Self {
+        Self {
+            mods,
+            codes: codes.to_vec(),
+        }
+    }
+}
+
+/// Handler recording every report it flushes, as if each one was sent right away.
+pub struct MockHandler {
+    registered: Vec<KeyCode>,
+    /// Codes registered since the last report, kept in the next one even if unregistered.
+    pending: Vec<KeyCode>,
+    temp: Vec<KeyCode>,
+    weak_modifiers: Modifiers,
+    suppressed_modifiers: Modifiers,
+    pub reports: Vec<MockReport>,
+}
+
+impl MockHandler {
+    pub fn new() -> Self {
+        Self {
+            registered: Vec::new(),
+            pending: Vec::new(),
+            temp: Vec::new(),
+            weak_modifiers: Modifiers::empty(),
+            suppressed_modifiers: Modifiers::empty(),
+            reports: Vec::new(),
+        }
+    }
+
+    /// Reports that differ from the one before them, as the host sees changes.
+    pub fn changes(&self) -> Vec<MockReport> {
+        let mut changes: Vec<MockReport> = Vec::new();
+        for report in &self.reports {
+            if changes.last().is_none_or(|last| last != report) {
+                changes.push(report.clone());
+            }
+        }
+        changes
+    }
+}
+
+impl Handler for MockHandler {
+    fn register(&mut self, code: KeyCode) {
+        if !self.registered.contains(&code) {
+            self.registered.push(code);
+        }
+        self.pending.push(code);
+    }
+
+    fn temp_register(&mut self, code: KeyCode) {
+        self.temp.push(code);
+    }
+
+    fn unregister(&mut self, code: KeyCode) {
+        self.registered.retain(|&x| x != code);
+    }
+
+    fn add_weak_modifiers(&mut self, mods: Modifiers) {
+        self.weak_modifiers |= mods;
+    }
+
+    fn remove_weak_modifiers(&mut self, mods: Modifiers) {
+        self.weak_modifiers &= !mods;
+    }
+
+    fn add_suppressed_modifiers(&mut self, mods: Modifiers) {
+        self.suppressed_modifiers |= mods;
+    }
+
+    fn remove_suppressed_modifiers(&mut self, mods: Modifiers) {
+        self.suppressed_modifiers &= !mods;
+    }
+
+    fn flush(&mut self) {
+        let mut report = MockReport::new(Modifiers::empty(), &[]);
+        let codes = self
+            .registered
+            .iter()
+            .chain(&self.pending)
+            .chain(&self.temp);
+        for &code in codes {
+            match code.modifier_mask() {
+                Some(mask) => report.mods |= mask,
+                None if !report.codes.contains(&code) => report.codes.push(code),
+                None => {}
+            }
+        }
+        report.mods &= !self.suppressed_modifiers;
+        report.mods |= self.weak_modifiers;
+
+        self.reports.push(report);
+        self.pending.clear();
+        self.temp.clear();
+    }
+}
@@ -1,6 +1,11 @@
 pub mod usb;
 
-use usb::KeyCode;
+#[cfg(test)]
+pub(crate) mod mock;
+
+use usb::{KeyCode, Leds, Modifiers};
+
+use crate::gamepad::Axis;
 
 pub trait Interface {
     type Handler: Handler;
@@ -13,4 +18,28 @@ pub trait Handler {
     fn temp_register(&mut self, code: KeyCode);
     fn unregister(&mut self, code: KeyCode);
     fn flush(&mut self);
+
+    /// Adds modifiers sent along with a key, such as the shift of a shifted symbol. Does nothing
+    /// by default.
+    fn add_weak_modifiers(&mut self, _mods: Modifiers) {}
+    /// Does nothing by default.
+    fn remove_weak_modifiers(&mut self, _mods: Modifiers) {}
+    /// Adds modifiers left out of the reports even while registered, such as the ones triggering
+    /// a key override. Does nothing by default.
+    fn add_suppressed_modifiers(&mut self, _mods: Modifiers) {}
+    /// Does nothing by default.
+    fn remove_suppressed_modifiers(&mut self, _mods: Modifiers) {}
+    /// Sets the deflection of a gamepad axis. Does nothing by default.
+    fn set_axis(&mut self, _axis: Axis, _deflection: i8) {}
+
+    /// Whether the last flushed state has no keys or modifiers registered, so nothing changes
+    /// until something new is registered. Default: never, so the keyboard never stops scanning.
+    fn is_idle(&self) -> bool {
+        false
+    }
+
+    /// LEDs as last set by the host. Default: none.
+    fn leds(&self) -> Leds {
+        Leds::empty()
+    }
 }
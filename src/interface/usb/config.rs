@@ -3,7 +3,7 @@ use static_cell::StaticCell;
 
 use crate::interface::usb::handlers::OkeyRequestHandler;
 
-use super::report::REPORT_DESCRIPTOR;
+use super::report::{GAMEPAD_REPORT_DESCRIPTOR, REPORT_DESCRIPTOR};
 
 static REQUEST_HANDLER: StaticCell<OkeyRequestHandler> = StaticCell::new();
 
@@ -21,6 +21,9 @@ pub struct Config<'a> {
     serial_number: Option<&'a str>,
     /// Polling interval in ms. Default: 10.
     poll_interval: u8,
+    /// Whether to add a gamepad interface for [`Action::Axis`](crate::prelude::Action::Axis).
+    /// Default: false.
+    gamepad: bool,
 }
 
 #[cfg(feature = "defmt")]
@@ -28,13 +31,14 @@ impl<'a> defmt::Format for Config<'a> {
     fn format(&self, fmt: defmt::Formatter) {
         defmt::write!(
             fmt,
-            "Config {{ vid: {:#04x}, pid: {:#04x}, manufacturer: {:?}, product: {:?}, serial_number: {:?}, poll_interval: {} }}",
+            "Config {{ vid: {:#04x}, pid: {:#04x}, manufacturer: {:?}, product: {:?}, serial_number: {:?}, poll_interval: {}, gamepad: {} }}",
             self.vid,
             self.pid,
             self.manufacturer,
             self.product,
             self.serial_number,
-            self.poll_interval
+            self.poll_interval,
+            self.gamepad
         )
     }
 }
@@ -48,6 +52,7 @@ impl<'a> Config<'a> {
             product: None,
             serial_number: None,
             poll_interval: 10,
+            gamepad: false,
         }
     }
 
@@ -72,6 +77,15 @@ impl<'a> Config<'a> {
         (usb, hid)
     }
 
+    pub(super) fn gamepad_config(&self) -> Option<HidConfig<'a>> {
+        self.gamepad.then_some(HidConfig {
+            report_descriptor: GAMEPAD_REPORT_DESCRIPTOR,
+            request_handler: None,
+            poll_ms: self.poll_interval,
+            max_packet_size: 8,
+        })
+    }
+
     pub const fn pid(mut self, pid: u16) -> Self {
         self.pid = pid;
         self
@@ -107,6 +121,11 @@ impl<'a> Config<'a> {
         self.poll_interval = (1000 / hz) as u8;
         self
     }
+
+    pub const fn gamepad(mut self, enabled: bool) -> Self {
+        self.gamepad = enabled;
+        self
+    }
 }
 
 impl<'a> Default for Config<'a> {
@@ -7,6 +7,8 @@ use embassy_usb::{
 
 use crate::debug;
 
+use super::set_configured;
+
 /// TODO: This handler does nothing expect log some info, that might be an issue.
 pub struct OkeyRequestHandler;
 
@@ -43,19 +45,22 @@ impl RequestHandler for OkeyRequestHandler {
     }
 }
 
-/// TODO: This handler does nothing except log some info, that might be an issue.
+/// Tracks whether the device is configured, for split keyboards to tell which half is connected.
+///
+/// TODO: Otherwise this handler does nothing except log some info, that might be an issue.
 pub struct OkeyDeviceHandler;
 
 impl Handler for OkeyDeviceHandler {
     fn enabled(&mut self, enabled: bool) {
-        debug!(
-            "Call to Handler::enabled with enabled: {}. Doing nothing.",
-            enabled
-        );
+        debug!("Call to Handler::enabled with enabled: {}.", enabled);
+        if !enabled {
+            set_configured(false);
+        }
     }
 
     fn reset(&mut self) {
-        debug!("Call to Handler::reset. Doing nothing.")
+        debug!("Call to Handler::reset.");
+        set_configured(false);
     }
 
     fn addressed(&mut self, addr: u8) {
@@ -67,9 +72,10 @@ impl Handler for OkeyDeviceHandler {
 
     fn configured(&mut self, configured: bool) {
         debug!(
-            "Call to Handler::configured with configured: {}. Doing nothing.",
+            "Call to Handler::configured with configured: {}.",
             configured
-        )
+        );
+        set_configured(configured);
     }
 
     fn suspended(&mut self, suspended: bool) {
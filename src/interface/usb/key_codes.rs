@@ -355,6 +355,32 @@ impl KeyCode {
         self.is_modifier().then(|| u8::from(self) & 0x07)
     }
 
+    pub fn from_modifier_mask(mask: Modifiers) -> Option<Self> {
+        match mask {
+            Modifiers::LEFT_CONTROL => Some(KeyCode::LeftControl),
+            Modifiers::LEFT_SHIFT => Some(KeyCode::LeftShift),
+            Modifiers::LEFT_ALT => Some(KeyCode::LeftAlt),
+            Modifiers::LEFT_GUI => Some(KeyCode::LeftGUI),
+            Modifiers::RIGHT_CONTROL => Some(KeyCode::RightControl),
+            Modifiers::RIGHT_SHIFT => Some(KeyCode::RightShift),
+            Modifiers::RIGHT_ALT => Some(KeyCode::RightAlt),
+            Modifiers::RIGHT_GUI => Some(KeyCode::RightGUI),
+            _ => None,
+        }
+    }
+
+    pub fn is_letter(self) -> bool {
+        (u8::from(KeyCode::KeyboardA)..=u8::from(KeyCode::KeyboardZ)).contains(&u8::from(self))
+    }
+
+    pub fn is_digit(self) -> bool {
+        (u8::from(KeyCode::Keyboard1)..=u8::from(KeyCode::Keyboard0)).contains(&u8::from(self))
+    }
+
+    pub fn is_symbol(self) -> bool {
+        (u8::from(KeyCode::Minus)..=u8::from(KeyCode::Slash)).contains(&u8::from(self))
+    }
+
     pub fn is_modifier(self) -> bool {
         match self {
             KeyCode::LeftControl
@@ -409,4 +435,20 @@ mod tests {
             Some(Modifiers::RIGHT_GUI)
         );
     }
+
+    #[test]
+    fn test_from_modifier_mask() {
+        assert_eq!(
+            KeyCode::from_modifier_mask(Modifiers::LEFT_SHIFT),
+            Some(KeyCode::LeftShift)
+        );
+        assert_eq!(
+            KeyCode::from_modifier_mask(Modifiers::RIGHT_GUI),
+            Some(KeyCode::RightGUI)
+        );
+        assert_eq!(
+            KeyCode::from_modifier_mask(Modifiers::LEFT_SHIFT | Modifiers::LEFT_ALT),
+            None
+        );
+    }
 }
@@ -9,7 +9,7 @@ mod state;
 use core::cell::Cell;
 
 use critical_section::Mutex;
-use embassy_futures::join::join;
+use embassy_futures::join::join4;
 use embassy_usb::{
     Builder, UsbDevice,
     class::hid::{HidReader, HidReaderWriter, HidWriter},
@@ -17,31 +17,37 @@ use embassy_usb::{
 };
 use static_cell::StaticCell;
 
-use crate::{debug, interface::usb::handlers::OkeyDeviceHandler, trace};
+use crate::{debug, gamepad::Axis, interface::usb::handlers::OkeyDeviceHandler, trace, warn};
 
-use report::Report;
+use report::{GamepadReport, Report};
 
 use super::{Handler, Interface};
 
 pub use config::Config;
 pub use key_codes::KeyCode;
-pub use report::Modifiers;
+pub use report::{Leds, Modifiers};
 pub use state::State;
 
 static SHARED_REPORT: Mutex<Cell<Report>> = Mutex::new(Cell::new(Report::new()));
 static WAS_REPORT_SENT: Mutex<Cell<bool>> = Mutex::new(Cell::new(false));
+static HOST_LEDS: Mutex<Cell<Leds>> = Mutex::new(Cell::new(Leds::empty()));
+static IS_CONFIGURED: Mutex<Cell<bool>> = Mutex::new(Cell::new(false));
+static SHARED_GAMEPAD_REPORT: Mutex<Cell<GamepadReport>> =
+    Mutex::new(Cell::new(GamepadReport::new()));
 
 static DEVICE_HANDLER: StaticCell<OkeyDeviceHandler> = StaticCell::new();
 
 pub struct UsbInterface<'d, D: Driver<'d>> {
     device: UsbDevice<'d, D>,
-    _reader: HidReader<'d, D, 1>,
+    reader: HidReader<'d, D, 1>,
     writer: HidWriter<'d, D, 8>,
+    gamepad: Option<HidWriter<'d, D, 4>>,
 }
 
 impl<'d, D: Driver<'d>> UsbInterface<'d, D> {
     pub fn new(driver: D, config: Config<'d>, state: &'d mut State<'d>) -> Self {
         debug!("Building USB interface with config: {}", config);
+        let gamepad_config = config.gamepad_config();
         let (usb_config, hid_config) = config.split();
 
         let mut builder = Builder::new(
@@ -53,22 +59,34 @@ impl<'d, D: Driver<'d>> UsbInterface<'d, D> {
             &mut state.control_buf,
         );
 
-        // TODO: This handler does nothing except log some info, that might be an issue.
+        // TODO: This handler mostly logs some info, that might be an issue.
         builder.handler(DEVICE_HANDLER.try_init_with(|| OkeyDeviceHandler).unwrap());
 
-        let (_reader, writer) =
+        let (reader, writer) =
             HidReaderWriter::new(&mut builder, &mut state.hid_state, hid_config).split();
+        let gamepad = gamepad_config
+            .map(|config| HidWriter::new(&mut builder, &mut state.gamepad_state, config));
 
         let device = builder.build();
 
         Self {
             device,
-            _reader,
+            reader,
             writer,
+            gamepad,
         }
     }
 }
 
+/// Whether the host has configured the USB device, so the keyboard is connected to it.
+pub(crate) fn is_configured() -> bool {
+    critical_section::with(|cs| IS_CONFIGURED.borrow(cs).get())
+}
+
+fn set_configured(configured: bool) {
+    critical_section::with(|cs| IS_CONFIGURED.borrow(cs).set(configured));
+}
+
 impl<'d, D: Driver<'d>> Interface for UsbInterface<'d, D> {
     type Handler = UsbHandler;
 
@@ -92,13 +110,51 @@ impl<'d, D: Driver<'d>> Interface for UsbInterface<'d, D> {
             }
         };
 
-        (UsbHandler::new(), join(fut1, fut2))
+        let fut3 = async move {
+            let Some(mut writer) = self.gamepad else {
+                return;
+            };
+
+            debug!("Running USB gamepad report writer...");
+            loop {
+                writer.ready().await;
+
+                let report = critical_section::with(|cs| SHARED_GAMEPAD_REPORT.borrow(cs).get());
+
+                let _ = writer.write(report.as_slice()).await;
+            }
+        };
+
+        let fut4 = async move {
+            debug!("Running USB LED reader...");
+            loop {
+                let mut buf = [0; 1];
+                match self.reader.read(&mut buf).await {
+                    Ok(_) => {
+                        let leds = Leds::from_bits_truncate(buf[0]);
+                        trace!("Host set LEDs: {}", leds);
+                        critical_section::with(|cs| HOST_LEDS.borrow(cs).set(leds));
+                    }
+                    Err(_e) => {
+                        warn!("Failed to read LEDs: {}", _e);
+                        self.reader.ready().await;
+                    }
+                }
+            }
+        };
+
+        (UsbHandler::new(), join4(fut1, fut2, fut3, fut4))
     }
 }
 
 pub struct UsbHandler {
     persistent_report: Report,
     report: Report,
+    temp_report: Report,
+    gamepad: GamepadReport,
+    weak_modifiers: Modifiers,
+    suppressed_modifiers: Modifiers,
+    is_idle: bool,
 }
 
 impl UsbHandler {
@@ -106,6 +162,11 @@ impl UsbHandler {
         Self {
             persistent_report: Report::new(),
             report: Report::new(),
+            temp_report: Report::new(),
+            gamepad: GamepadReport::new(),
+            weak_modifiers: Modifiers::empty(),
+            suppressed_modifiers: Modifiers::empty(),
+            is_idle: true,
         }
     }
 }
@@ -118,23 +179,63 @@ impl Handler for UsbHandler {
     }
 
     fn temp_register(&mut self, code: KeyCode) {
-        let _ = self.report.add(code);
+        let _ = self.temp_report.add(code);
     }
 
     fn unregister(&mut self, code: KeyCode) {
         let _ = self.persistent_report.remove(code);
     }
 
+    fn add_weak_modifiers(&mut self, mods: Modifiers) {
+        self.weak_modifiers |= mods;
+    }
+
+    fn remove_weak_modifiers(&mut self, mods: Modifiers) {
+        self.weak_modifiers &= !mods;
+    }
+
+    fn add_suppressed_modifiers(&mut self, mods: Modifiers) {
+        self.suppressed_modifiers |= mods;
+    }
+
+    fn remove_suppressed_modifiers(&mut self, mods: Modifiers) {
+        self.suppressed_modifiers &= !mods;
+    }
+
+    fn set_axis(&mut self, axis: Axis, deflection: i8) {
+        self.gamepad.set(axis, deflection);
+    }
+
     fn flush(&mut self) {
-        trace!("Flushing USB report: {}", self.report);
+        // Checking whether the previous report was sent and replacing it has to happen at once,
+        // otherwise codes registered for a single report could be dropped before being sent.
+        let report = critical_section::with(|cs| {
+            if WAS_REPORT_SENT.borrow(cs).replace(false) {
+                self.report = self.persistent_report;
+            }
 
-        let sent = critical_section::with(|cs| {
-            SHARED_REPORT.borrow(cs).set(self.report);
-            WAS_REPORT_SENT.borrow(cs).replace(false)
+            let _ = self.report.extend(&self.temp_report);
+
+            let mut report = self.report;
+            report.remove_modifiers(self.suppressed_modifiers);
+            report.add_modifiers(self.weak_modifiers);
+
+            SHARED_REPORT.borrow(cs).set(report);
+            SHARED_GAMEPAD_REPORT.borrow(cs).set(self.gamepad);
+            report
         });
 
-        if sent {
-            self.report = self.persistent_report;
-        }
+        trace!("Flushed USB report: {}", report);
+
+        self.temp_report = Report::new();
+        self.is_idle = report.is_empty() && self.gamepad.is_centered();
+    }
+
+    fn is_idle(&self) -> bool {
+        self.is_idle
+    }
+
+    fn leds(&self) -> Leds {
+        critical_section::with(|cs| HOST_LEDS.borrow(cs).get())
     }
 }
@@ -1,7 +1,7 @@
 //! QMK/TMK style keycodes for ease of configuration.
 
 use crate::action::Action;
-use crate::interface::usb::KeyCode;
+use crate::interface::usb::{KeyCode, Modifiers};
 use crate::map::Opacity;
 
 pub type KeyAction = Opacity<Option<Action>>;
@@ -49,15 +49,139 @@ pub const fn TH(tapped: KeyCode, held: KeyCode) -> Opacity<Option<Action>> {
     }))
 }
 
-macro_rules! define_keys {
-    ($(#[doc = $doc:literal] $ident:ident $(($($alias:ident),+))? => $code:expr),* $(,)?) => {
+/// Repeat the last key.
+///
+/// You might want to use the alias: [`QK_REP`].
+pub const QK_REPEAT_KEY: Opacity<Option<Action>> = Opacity::Opaque(Some(Action::Repeat));
+/// Repeat the last key.
+///
+/// Alias for [`QK_REPEAT_KEY`].
+pub const QK_REP: Opacity<Option<Action>> = QK_REPEAT_KEY;
+
+/// Send the opposite of the last key.
+///
+/// You might want to use the alias: [`QK_AREP`].
+pub const QK_ALT_REPEAT_KEY: Opacity<Option<Action>> = Opacity::Opaque(Some(Action::AltRepeat));
+/// Send the opposite of the last key.
+///
+/// Alias for [`QK_ALT_REPEAT_KEY`].
+pub const QK_AREP: Opacity<Option<Action>> = QK_ALT_REPEAT_KEY;
+
+/// Registers the shifted code when held past the auto shift timeout.
+#[allow(non_snake_case)]
+pub const fn AS(code: KeyCode) -> Opacity<Option<Action>> {
+    Opacity::Opaque(Some(Action::AutoShift(code)))
+}
+
+/// Hold left control while registering `action`.
+#[allow(non_snake_case)]
+pub const fn LCTL(action: KeyAction) -> KeyAction {
+    with_modifiers(action, Modifiers::LEFT_CONTROL)
+}
+
+/// Hold left shift while registering `action`.
+///
+/// You might want to use the alias: [`S`].
+#[allow(non_snake_case)]
+pub const fn LSFT(action: KeyAction) -> KeyAction {
+    with_modifiers(action, Modifiers::LEFT_SHIFT)
+}
+
+/// Hold left shift while registering `action`.
+///
+/// Alias for [`LSFT`].
+#[allow(non_snake_case)]
+pub const fn S(action: KeyAction) -> KeyAction {
+    LSFT(action)
+}
+
+/// Hold left alt while registering `action`.
+#[allow(non_snake_case)]
+pub const fn LALT(action: KeyAction) -> KeyAction {
+    with_modifiers(action, Modifiers::LEFT_ALT)
+}
+
+/// Hold left GUI while registering `action`.
+#[allow(non_snake_case)]
+pub const fn LGUI(action: KeyAction) -> KeyAction {
+    with_modifiers(action, Modifiers::LEFT_GUI)
+}
+
+/// Hold right control while registering `action`.
+#[allow(non_snake_case)]
+pub const fn RCTL(action: KeyAction) -> KeyAction {
+    with_modifiers(action, Modifiers::RIGHT_CONTROL)
+}
+
+/// Hold right shift while registering `action`.
+#[allow(non_snake_case)]
+pub const fn RSFT(action: KeyAction) -> KeyAction {
+    with_modifiers(action, Modifiers::RIGHT_SHIFT)
+}
+
+/// Hold right alt while registering `action`.
+#[allow(non_snake_case)]
+pub const fn RALT(action: KeyAction) -> KeyAction {
+    with_modifiers(action, Modifiers::RIGHT_ALT)
+}
+
+/// Hold right GUI while registering `action`.
+#[allow(non_snake_case)]
+pub const fn RGUI(action: KeyAction) -> KeyAction {
+    with_modifiers(action, Modifiers::RIGHT_GUI)
+}
+
+/// Hold left control and left shift while registering `action`.
+#[allow(non_snake_case)]
+pub const fn C_S(action: KeyAction) -> KeyAction {
+    LCTL(LSFT(action))
+}
+
+/// Hold left control, left shift and left alt while registering `action`.
+#[allow(non_snake_case)]
+pub const fn MEH(action: KeyAction) -> KeyAction {
+    LCTL(LSFT(LALT(action)))
+}
+
+/// Hold left control, left shift, left alt and left GUI while registering `action`.
+#[allow(non_snake_case)]
+pub const fn HYPR(action: KeyAction) -> KeyAction {
+    MEH(LGUI(action))
+}
+
+const fn with_modifiers(action: KeyAction, mods: Modifiers) -> KeyAction {
+    match action {
+        Opacity::Opaque(Some(Action::Code(code))) => {
+            Opacity::Opaque(Some(Action::Modded { mods, code }))
+        }
+        Opacity::Opaque(Some(Action::Modded { mods: inner, code })) => {
+            Opacity::Opaque(Some(Action::Modded {
+                mods: inner.union(mods),
+                code,
+            }))
+        }
+        _ => panic!("Modifiers can only be added to key codes"),
+    }
+}
+
+/// Toggle caps word.
+///
+/// You might want to use the alias: [`CW_TOGG`].
+pub const QK_CAPS_WORD_TOGGLE: Opacity<Option<Action>> = Opacity::Opaque(Some(Action::CapsWord));
+/// Toggle caps word.
+///
+/// Alias for [`QK_CAPS_WORD_TOGGLE`].
+pub const CW_TOGG: Opacity<Option<Action>> = QK_CAPS_WORD_TOGGLE;
+
+macro_rules! define_key_actions {
+    ($(#[doc = $doc:literal] $ident:ident $(($($alias:ident),+))? => $action:expr),* $(,)?) => {
         $(
             #[doc = $doc]
             $(
                 #[doc = ""]
                 #[doc = format_alias_intro!($($alias),+)]
             )?
-            pub const $ident: KeyAction = Opacity::Opaque(Some(Action::Code($code)));
+            pub const $ident: KeyAction = $action;
 
             $($(
                 #[doc = $doc]
@@ -69,6 +193,17 @@ macro_rules! define_keys {
     };
 }
 
+macro_rules! define_keys {
+    ($(#[doc = $doc:literal] $ident:ident $(($($alias:ident),+))? => $code:expr),* $(,)?) => {
+        define_key_actions! {
+            $(
+                #[doc = $doc]
+                $ident $(($($alias),+))? => Opacity::Opaque(Some(Action::Code($code)))
+            ),*
+        }
+    };
+}
+
 macro_rules! format_alias_intro {
     ($alias:ident) => {
         concat!("You might want to use the alias: [`", stringify!($alias), "`].")
@@ -427,3 +562,84 @@ define_keys! {
     /// Keyboard `Right GUI`.
     KC_RIGHT_GUI (KC_RGUI, KC_RCMD, KC_RWIN) => KeyCode::RightGUI,
 }
+
+define_key_actions! {
+    /// Keyboard `~`.
+    KC_TILDE (KC_TILD) => LSFT(KC_GRAVE),
+    /// Keyboard `!`.
+    KC_EXCLAIM (KC_EXLM) => LSFT(KC_1),
+    /// Keyboard `@`.
+    KC_AT => LSFT(KC_2),
+    /// Keyboard `#`.
+    KC_HASH => LSFT(KC_3),
+    /// Keyboard `$`.
+    KC_DOLLAR (KC_DLR) => LSFT(KC_4),
+    /// Keyboard `%`.
+    KC_PERCENT (KC_PERC) => LSFT(KC_5),
+    /// Keyboard `^`.
+    KC_CIRCUMFLEX (KC_CIRC) => LSFT(KC_6),
+    /// Keyboard `&`.
+    KC_AMPERSAND (KC_AMPR) => LSFT(KC_7),
+    /// Keyboard `*`.
+    KC_ASTERISK (KC_ASTR) => LSFT(KC_8),
+    /// Keyboard `(`.
+    KC_LEFT_PAREN (KC_LPRN) => LSFT(KC_9),
+    /// Keyboard `)`.
+    KC_RIGHT_PAREN (KC_RPRN) => LSFT(KC_0),
+    /// Keyboard `_`.
+    KC_UNDERSCORE (KC_UNDS) => LSFT(KC_MINUS),
+    /// Keyboard `+`.
+    KC_PLUS => LSFT(KC_EQUAL),
+    /// Keyboard `{`.
+    KC_LEFT_CURLY_BRACE (KC_LCBR) => LSFT(KC_LEFT_BRACKET),
+    /// Keyboard `}`.
+    KC_RIGHT_CURLY_BRACE (KC_RCBR) => LSFT(KC_RIGHT_BRACKET),
+    /// Keyboard `|`.
+    KC_PIPE => LSFT(KC_BACKSLASH),
+    /// Keyboard `:`.
+    KC_COLON (KC_COLN) => LSFT(KC_SEMICOLON),
+    /// Keyboard `"`.
+    KC_DOUBLE_QUOTE (KC_DQUO, KC_DQT) => LSFT(KC_QUOTE),
+    /// Keyboard `<`.
+    KC_LEFT_ANGLE_BRACKET (KC_LABK, KC_LT) => LSFT(KC_COMMA),
+    /// Keyboard `>`.
+    KC_RIGHT_ANGLE_BRACKET (KC_RABK, KC_GT) => LSFT(KC_DOT),
+    /// Keyboard `?`.
+    KC_QUESTION (KC_QUES) => LSFT(KC_SLASH),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modifiers_wrap_key_code() {
+        assert_eq!(
+            LCTL(KC_C),
+            KeyAction::Opaque(Some(Action::Modded {
+                mods: Modifiers::LEFT_CONTROL,
+                code: KeyCode::KeyboardC
+            }))
+        );
+    }
+
+    #[test]
+    fn nested_modifiers_are_combined() {
+        assert_eq!(
+            HYPR(KC_A),
+            KeyAction::Opaque(Some(Action::Modded {
+                mods: Modifiers::LEFT_CONTROL
+                    | Modifiers::LEFT_SHIFT
+                    | Modifiers::LEFT_ALT
+                    | Modifiers::LEFT_GUI,
+                code: KeyCode::KeyboardA
+            }))
+        );
+    }
+
+    #[test]
+    fn shifted_symbols() {
+        assert_eq!(KC_EXLM, LSFT(KC_1));
+        assert_eq!(KC_LPRN, S(KC_9));
+    }
+}
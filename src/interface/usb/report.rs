@@ -1,5 +1,7 @@
 use core::fmt::{Debug, Formatter};
 
+use crate::gamepad::Axis;
+
 use super::KeyCode;
 
 /// No event indicated.
@@ -46,6 +48,25 @@ pub const REPORT_DESCRIPTOR: &[u8] = &[
     0xC0, // End Collection
 ];
 
+pub const GAMEPAD_REPORT_DESCRIPTOR: &[u8] = &[
+    0x05, 0x01, // Usage Page (Generic Desktop)
+    0x09, 0x05, // Usage (Gamepad)
+    0xA1, 0x01, // Collection (Application)
+    0x09, 0x01, //   Usage (Pointer)
+    0xA1, 0x00, //   Collection (Physical)
+    0x09, 0x30, //     Usage (X)
+    0x09, 0x31, //     Usage (Y)
+    0x09, 0x32, //     Usage (Z)
+    0x09, 0x35, //     Usage (Rz)
+    0x15, 0x81, //     Logical Minimum (-127)
+    0x25, 0x7F, //     Logical Maximum (127)
+    0x75, 0x08, //     Report Size (8)
+    0x95, 0x04, //     Report Count (4)
+    0x81, 0x02, //     Input (Data, Variable, Absolute)
+    0xC0, //   End Collection
+    0xC0, // End Collection
+];
+
 #[derive(Clone, Copy, Debug)]
 pub struct ReportError;
 
@@ -75,21 +96,49 @@ impl Report {
         &self.inner.key_codes[..self.len]
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.modifiers().is_empty() && self.len == 0
+    }
+
     pub fn add(&mut self, code: KeyCode) -> Result<(), ReportError> {
         if let Some(mask) = code.modifier_mask() {
-            self.inner.modifiers |= mask
+            self.inner.modifiers |= mask;
+            Ok(())
         } else {
-            if self.len >= self.inner.key_codes.len() {
-                return Err(ReportError);
-            }
+            self.push(u8::from(code))
+        }
+    }
+
+    /// Adds all modifiers and key codes of `other`.
+    pub fn extend(&mut self, other: &Report) -> Result<(), ReportError> {
+        self.inner.modifiers |= other.modifiers();
+
+        for code in other.key_codes() {
+            self.push(*code)?;
+        }
+
+        Ok(())
+    }
 
-            self.inner.key_codes[self.len] = u8::from(code);
-            self.len += 1;
+    fn push(&mut self, code: u8) -> Result<(), ReportError> {
+        if self.len >= self.inner.key_codes.len() {
+            return Err(ReportError);
         }
 
+        self.inner.key_codes[self.len] = code;
+        self.len += 1;
+
         Ok(())
     }
 
+    pub fn add_modifiers(&mut self, mods: Modifiers) {
+        self.inner.modifiers |= mods
+    }
+
+    pub fn remove_modifiers(&mut self, mods: Modifiers) {
+        self.inner.modifiers &= !mods
+    }
+
     pub fn remove(&mut self, code: KeyCode) -> Result<(), ReportError> {
         if let Some(mask) = code.modifier_mask() {
             self.inner.modifiers &= !mask
@@ -174,6 +223,31 @@ impl Default for Inner {
     }
 }
 
+/// Deflection of every gamepad axis, in the order of [`Axis::ALL`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GamepadReport {
+    axes: [u8; 4],
+}
+
+impl GamepadReport {
+    pub const fn new() -> Self {
+        Self { axes: [0; 4] }
+    }
+
+    pub const fn as_slice(&self) -> &[u8] {
+        &self.axes
+    }
+
+    pub fn set(&mut self, axis: Axis, deflection: i8) {
+        self.axes[axis as usize] = deflection as u8;
+    }
+
+    pub fn is_centered(&self) -> bool {
+        self.axes == [0; 4]
+    }
+}
+
 bitflags::bitflags! {
     #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     pub struct Modifiers: u8 {
@@ -194,3 +268,73 @@ impl defmt::Format for Modifiers {
         defmt::write!(fmt, "Modifiers(0x{:08x})", self.bits())
     }
 }
+
+bitflags::bitflags! {
+    /// LEDs of the keyboard as set by the host.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Leds: u8 {
+        const NUM_LOCK = 0b0000_0001;
+        const CAPS_LOCK = 0b0000_0010;
+        const SCROLL_LOCK = 0b0000_0100;
+        const COMPOSE = 0b0000_1000;
+        const KANA = 0b0001_0000;
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Leds {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "Leds(0x{:02x})", self.bits())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_report_is_empty() {
+        assert!(Report::new().is_empty());
+    }
+
+    #[test]
+    fn extend_adds_codes_and_modifiers() {
+        let mut report = Report::new();
+        report.add(KeyCode::KeyboardA).unwrap();
+
+        let mut other = Report::new();
+        other.add(KeyCode::LeftShift).unwrap();
+        other.add(KeyCode::KeyboardB).unwrap();
+
+        report.extend(&other).unwrap();
+
+        assert_eq!(report.modifiers(), Modifiers::LEFT_SHIFT);
+        assert_eq!(
+            report.key_codes(),
+            &[u8::from(KeyCode::KeyboardA), u8::from(KeyCode::KeyboardB)]
+        );
+    }
+
+    #[test]
+    fn extend_fails_when_full() {
+        let mut report = Report::new();
+        let mut other = Report::new();
+
+        for _ in 0..4 {
+            report.add(KeyCode::KeyboardA).unwrap();
+            other.add(KeyCode::KeyboardB).unwrap();
+        }
+
+        assert!(report.extend(&other).is_err());
+    }
+
+    #[test]
+    fn gamepad_report_axes() {
+        let mut report = GamepadReport::new();
+        report.set(Axis::LeftY, -127);
+        report.set(Axis::RightX, 64);
+
+        assert_eq!(report.as_slice(), &[0, 0x81, 64, 0]);
+        assert!(!report.is_centered());
+    }
+}
@@ -6,6 +6,7 @@ pub struct State<'a> {
     pub(super) msos_descriptor_buf: [u8; 256],
     pub(super) control_buf: [u8; 64],
     pub(super) hid_state: hid::State<'a>,
+    pub(super) gamepad_state: hid::State<'a>,
 }
 
 impl<'a> State<'a> {
@@ -16,6 +17,7 @@ impl<'a> State<'a> {
             msos_descriptor_buf: [0; 256],
             control_buf: [0; 64],
             hid_state: hid::State::new(),
+            gamepad_state: hid::State::new(),
         }
     }
 }
@@ -0,0 +1,121 @@
+use crate::interface::usb::{KeyCode, Modifiers};
+
+/// Replaces a key code with another while a set of modifiers is held, e.g. sending `Delete`
+/// for `Shift` + `Backspace`.
+///
+/// Modifiers are matched regardless of side, so [`Modifiers::LEFT_SHIFT`] is satisfied by either
+/// shift key.
+///
+/// The override is only checked when the trigger key is pressed. Modifiers pressed while the
+/// trigger is already held are sent along with it, and releasing them while the override is
+/// active keeps sending the replacement until the trigger is released.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct KeyOverride {
+    /// Key code that triggers the override.
+    trigger: KeyCode,
+    /// Modifiers that all need to be held for the override to trigger.
+    trigger_modifiers: Modifiers,
+    /// Modifiers that prevent the override from triggering if any of them are held. Default: none.
+    negative_modifiers: Modifiers,
+    /// Key code sent instead of the trigger.
+    replacement: KeyCode,
+    /// Modifiers sent together with the replacement. Default: none.
+    replacement_modifiers: Modifiers,
+}
+
+impl KeyOverride {
+    pub const fn new(trigger_modifiers: Modifiers, trigger: KeyCode, replacement: KeyCode) -> Self {
+        Self {
+            trigger,
+            trigger_modifiers,
+            negative_modifiers: Modifiers::empty(),
+            replacement,
+            replacement_modifiers: Modifiers::empty(),
+        }
+    }
+
+    pub const fn negative_modifiers(mut self, mods: Modifiers) -> Self {
+        self.negative_modifiers = mods;
+        self
+    }
+
+    pub const fn replacement_modifiers(mut self, mods: Modifiers) -> Self {
+        self.replacement_modifiers = mods;
+        self
+    }
+
+    pub(crate) fn replacement(&self) -> KeyCode {
+        self.replacement
+    }
+
+    pub(crate) fn replacement_mods(&self) -> Modifiers {
+        self.replacement_modifiers
+    }
+
+    /// Whether the override triggers for `code` pressed while `held` modifiers are active.
+    pub(crate) fn matches(&self, code: KeyCode, held: Modifiers) -> bool {
+        let held = sideless(held);
+
+        code == self.trigger
+            && held.contains(sideless(self.trigger_modifiers))
+            && !held.intersects(sideless(self.negative_modifiers))
+    }
+
+    /// The subset of `held` modifiers that triggered the override and should be suppressed
+    /// while it is active.
+    pub(crate) fn suppressed(&self, held: Modifiers) -> Modifiers {
+        held & both_sides(self.trigger_modifiers)
+    }
+}
+
+fn sideless(mods: Modifiers) -> Modifiers {
+    Modifiers::from_bits_truncate((mods.bits() | (mods.bits() >> 4)) & 0x0F)
+}
+
+fn both_sides(mods: Modifiers) -> Modifiers {
+    let left = sideless(mods).bits();
+    Modifiers::from_bits_truncate(left | (left << 4))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHIFT_BACKSPACE: KeyOverride =
+        KeyOverride::new(Modifiers::LEFT_SHIFT, KeyCode::Backspace, KeyCode::Delete)
+            .negative_modifiers(Modifiers::LEFT_CONTROL);
+
+    #[test]
+    fn matches_with_either_side() {
+        assert!(SHIFT_BACKSPACE.matches(KeyCode::Backspace, Modifiers::LEFT_SHIFT));
+        assert!(SHIFT_BACKSPACE.matches(KeyCode::Backspace, Modifiers::RIGHT_SHIFT));
+    }
+
+    #[test]
+    fn does_not_match_without_modifiers() {
+        assert!(!SHIFT_BACKSPACE.matches(KeyCode::Backspace, Modifiers::empty()));
+        assert!(!SHIFT_BACKSPACE.matches(KeyCode::Backspace, Modifiers::LEFT_ALT));
+    }
+
+    #[test]
+    fn does_not_match_other_key() {
+        assert!(!SHIFT_BACKSPACE.matches(KeyCode::Delete, Modifiers::LEFT_SHIFT));
+    }
+
+    #[test]
+    fn does_not_match_with_negative_modifiers() {
+        assert!(!SHIFT_BACKSPACE.matches(
+            KeyCode::Backspace,
+            Modifiers::LEFT_SHIFT | Modifiers::RIGHT_CONTROL
+        ));
+    }
+
+    #[test]
+    fn suppresses_only_triggering_modifiers() {
+        assert_eq!(
+            SHIFT_BACKSPACE.suppressed(Modifiers::RIGHT_SHIFT | Modifiers::LEFT_ALT),
+            Modifiers::RIGHT_SHIFT
+        );
+    }
+}
@@ -4,11 +4,18 @@ pub mod prelude;
 pub use interface::usb::qmk_key_codes;
 
 mod action;
+mod auto_shift;
+mod caps_word;
+mod encoder;
 mod event;
+mod gamepad;
 mod interface;
+mod key_override;
 mod macros;
 mod map;
+mod repeat;
 mod scan;
+mod split;
 
 use embassy_futures::join;
 use embassy_time::{Duration, Instant, Ticker};
@@ -17,19 +24,31 @@ use embassy_time::{Duration, Instant, Ticker};
 use defmt;
 
 use action::Action;
+use auto_shift::AutoShiftConfig;
+use caps_word::{CapsWord, CapsWordConfig};
+use encoder::{Encode, Encoders, MappedEncoders};
 use event::Event;
-use interface::{Handler, Interface};
-use map::{ActionMap, LayeredMap};
-use scan::Scan;
+use gamepad::{Axis, AxisDirection, GamepadConfig};
+use interface::{
+    Handler, Interface,
+    usb::{KeyCode, Modifiers},
+};
+use key_override::KeyOverride;
+use map::{ActionMap, EncoderMap, LayeredMap};
+use repeat::RepeatConfig;
+use scan::{FULL_TRAVEL, Scan, ScanErrorPolicy};
+use split::SplitState;
 
 pub const SCAN_INTERVAL: Duration = Duration::from_millis(1);
 
 pub const DEFAULT_TAP_TIMEOUT: Duration = Duration::from_millis(200);
 
-pub struct Keyboard<S, M, I, const W: usize, const H: usize> {
+pub struct Keyboard<S, M, I, const W: usize, const H: usize, E = ()> {
     scanner: S,
     mapper: M,
     interface: I,
+    encoders: E,
+    options: Options,
 }
 
 impl<S, I, const W: usize, const H: usize, const D: usize> Keyboard<S, LayeredMap<W, H, D>, I, W, H>
@@ -42,9 +61,71 @@ where
             scanner,
             mapper: mapper.into(),
             interface,
+            encoders: (),
+            options: Options::new(),
         }
     }
 
+    pub fn encoders<E: Encode, const N: usize>(
+        self,
+        encoders: [E; N],
+        map: impl Into<EncoderMap<N, D>>,
+    ) -> Keyboard<S, LayeredMap<W, H, D>, I, W, H, MappedEncoders<E, N, D>> {
+        Keyboard {
+            scanner: self.scanner,
+            mapper: self.mapper,
+            interface: self.interface,
+            encoders: MappedEncoders::new(encoders, map.into()),
+            options: self.options,
+        }
+    }
+}
+
+impl<S, I, E, const W: usize, const H: usize, const D: usize>
+    Keyboard<S, LayeredMap<W, H, D>, I, W, H, E>
+where
+    S: Scan<W, H>,
+    I: Interface,
+    E: Encoders,
+{
+    pub fn caps_word(mut self, config: CapsWordConfig) -> Self {
+        self.options.caps_word = config;
+        self
+    }
+
+    pub fn auto_shift(mut self, config: AutoShiftConfig) -> Self {
+        self.options.auto_shift = config;
+        self
+    }
+
+    pub fn repeat(mut self, config: RepeatConfig) -> Self {
+        self.options.repeat = config;
+        self
+    }
+
+    pub fn key_overrides(mut self, overrides: &'static [KeyOverride]) -> Self {
+        self.options.key_overrides = overrides;
+        self
+    }
+
+    pub fn scan_error_policy(mut self, policy: ScanErrorPolicy) -> Self {
+        self.options.scan_error_policy = policy;
+        self
+    }
+
+    /// Enables [`Action::Axis`], which also needs an interface with a gamepad.
+    pub fn gamepad(mut self, config: GamepadConfig) -> Self {
+        self.options.gamepad = Some(config);
+        self
+    }
+
+    /// Keeps the state of this primary half up to date, for a [`Remote`](split::Remote) to send to
+    /// the secondary half.
+    pub fn split_state(mut self, state: &'static SplitState) -> Self {
+        self.options.split_state = Some(state);
+        self
+    }
+
     pub async fn run(self) -> ! {
         info!("Running keyboard main task...");
         let (board, fut) = self.morph();
@@ -55,38 +136,96 @@ where
     fn morph(
         self,
     ) -> (
-        RunningKeyboard<S, LayeredMap<W, H, D>, I::Handler, W, H>,
+        RunningKeyboard<S, LayeredMap<W, H, D>, I::Handler, W, H, E>,
         impl Future,
     ) {
         debug!("Running interface tasks...");
         let (handler, fut) = self.interface.start();
 
         (
-            RunningKeyboard::new(self.scanner, self.mapper, handler),
+            RunningKeyboard::new(
+                self.scanner,
+                self.mapper,
+                handler,
+                self.encoders,
+                self.options,
+            ),
             fut,
         )
     }
 }
 
-struct RunningKeyboard<S, M, T, const W: usize, const H: usize> {
+#[derive(Clone, Copy, Debug)]
+struct Options {
+    caps_word: CapsWordConfig,
+    auto_shift: AutoShiftConfig,
+    repeat: RepeatConfig,
+    key_overrides: &'static [KeyOverride],
+    scan_error_policy: ScanErrorPolicy,
+    gamepad: Option<GamepadConfig>,
+    split_state: Option<&'static SplitState>,
+}
+
+impl Options {
+    const fn new() -> Self {
+        Self {
+            caps_word: CapsWordConfig::new(),
+            auto_shift: AutoShiftConfig::none(),
+            repeat: RepeatConfig::new(),
+            key_overrides: &[],
+            scan_error_policy: ScanErrorPolicy::Release,
+            gamepad: None,
+            split_state: None,
+        }
+    }
+}
+
+struct RunningKeyboard<S, M, T, const W: usize, const H: usize, E> {
     scanner: S,
     mapper: M,
     handler: T,
+    encoders: E,
     pressed: [[Option<Pressed>; W]; H],
+    modifiers: Modifiers,
+    caps_word: CapsWord,
+    auto_shift: AutoShiftConfig,
+    repeat: RepeatConfig,
+    key_overrides: &'static [KeyOverride],
+    scan_error_policy: ScanErrorPolicy,
+    gamepad: Option<GamepadConfig>,
+    split_state: Option<&'static SplitState>,
+    last_key: Option<(KeyCode, Modifiers)>,
 }
 
-impl<S, T, const W: usize, const H: usize, const D: usize>
-    RunningKeyboard<S, LayeredMap<W, H, D>, T, W, H>
+impl<S, T, E, const W: usize, const H: usize, const D: usize>
+    RunningKeyboard<S, LayeredMap<W, H, D>, T, W, H, E>
 where
     S: Scan<W, H>,
     T: Handler,
+    E: Encoders,
 {
-    fn new(scanner: S, mapper: LayeredMap<W, H, D>, handler: T) -> Self {
+    fn new(
+        scanner: S,
+        mapper: LayeredMap<W, H, D>,
+        handler: T,
+        encoders: E,
+        options: Options,
+    ) -> Self {
         Self {
             scanner,
             mapper,
             handler,
+            encoders,
             pressed: [[None; W]; H],
+            modifiers: Modifiers::empty(),
+            caps_word: CapsWord::new(options.caps_word),
+            auto_shift: options.auto_shift,
+            repeat: options.repeat,
+            key_overrides: options.key_overrides,
+            scan_error_policy: options.scan_error_policy,
+            gamepad: options.gamepad,
+            split_state: options.split_state,
+            last_key: None,
         }
     }
 
@@ -98,16 +237,61 @@ where
         let mut ticker = Ticker::every(SCAN_INTERVAL);
 
         loop {
-            self.scanner.scan(scan).await;
-            self.process_events(scan, prev_scan);
-            self.handler.flush();
+            self.scan(scan, prev_scan).await;
+            self.process(scan, prev_scan);
 
             core::mem::swap(&mut scan, &mut prev_scan);
 
+            if self.is_idle(prev_scan) && self.scanner.wait_for_press().await {
+                trace!("Woke up from idle");
+                ticker.reset();
+            }
+
             ticker.next().await;
         }
     }
 
+    /// Processes everything that changed since the previous scan and flushes the result.
+    fn process(&mut self, scan: &[[bool; W]; H], prev_scan: &[[bool; W]; H]) {
+        self.process_events(scan, prev_scan);
+        self.process_encoders();
+        self.process_axes(scan);
+        self.handler.flush();
+
+        if let Some(state) = self.split_state {
+            state.publish(self.mapper.active_layers(), self.handler.leds());
+        }
+    }
+
+    /// Scans the matrix into `scan`, handling failures according to the scan error policy.
+    async fn scan(&mut self, scan: &mut [[bool; W]; H], prev_scan: &[[bool; W]; H]) {
+        let mut retries = 0;
+
+        while let Err(_e) = self.scanner.scan(scan).await {
+            error!("Failed to scan matrix: {}", defmt::Debug2Format(&_e));
+
+            match self.scan_error_policy {
+                ScanErrorPolicy::Retry(max) if retries < max => retries += 1,
+                ScanErrorPolicy::Keep => {
+                    *scan = *prev_scan;
+                    break;
+                }
+                ScanErrorPolicy::Release | ScanErrorPolicy::Retry(_) => {
+                    *scan = [[false; W]; H];
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Whether nothing can change until a key is pressed.
+    fn is_idle(&self, scan: &[[bool; W]; H]) -> bool {
+        self.encoders.count() == 0
+            && self.handler.is_idle()
+            && scan.iter().flatten().all(|x| !x)
+            && self.pressed.iter().flatten().all(Option::is_none)
+    }
+
     fn process_events(&mut self, scan: &[[bool; W]; H], prev_scan: &[[bool; W]; H]) {
         for y in 0..H {
             for x in 0..W {
@@ -118,6 +302,94 @@ where
         }
     }
 
+    fn process_encoders(&mut self) {
+        for i in 0..self.encoders.count() {
+            let direction = match self.encoders.poll(i) {
+                Ok(Some(direction)) => direction,
+                Ok(None) => continue,
+                Err(_e) => {
+                    error!("Failed to poll encoder {}: {}", i, defmt::Debug2Format(&_e));
+                    continue;
+                }
+            };
+
+            let active_layers = self.mapper.active_layers();
+            if let Some(action) = self.encoders.get(i, direction, active_layers) {
+                debug!(
+                    "Processing encoder {} turned {} with action {}",
+                    i, direction, action
+                );
+                self.process_action_tapped(action);
+            }
+        }
+    }
+
+    /// Sets every gamepad axis from how far the keys bound to it are pressed.
+    fn process_axes(&mut self, scan: &[[bool; W]; H]) {
+        let Some(gamepad) = self.gamepad else {
+            return;
+        };
+
+        let mut axes = [0i16; Axis::ALL.len()];
+        for (y, row) in scan.iter().enumerate() {
+            for (x, &pressed) in row.iter().enumerate() {
+                let action = match self.get_pressed(x, y) {
+                    Some(pressed) => Some(pressed.action),
+                    None => self.mapper.get(x as u8, y as u8),
+                };
+                let Some(Action::Axis { axis, direction }) = action else {
+                    continue;
+                };
+
+                let travel =
+                    self.scanner
+                        .travel(x, y)
+                        .unwrap_or(if pressed { FULL_TRAVEL } else { 0 });
+                let deflection = gamepad.deflection(travel) as i16;
+                axes[axis as usize] += match direction {
+                    AxisDirection::Negative => -deflection,
+                    AxisDirection::Positive => deflection,
+                };
+            }
+        }
+
+        for (axis, deflection) in Axis::ALL.into_iter().zip(axes) {
+            let max = gamepad::MAX_DEFLECTION as i16;
+            self.handler
+                .set_axis(axis, deflection.clamp(-max, max) as i8);
+        }
+    }
+
+    /// Processes an action that has no position in the matrix and is pressed and released at
+    /// once, such as one bound to an encoder.
+    fn process_action_tapped(&mut self, action: Action) {
+        match action {
+            Action::Code(code) | Action::AutoShift(code) | Action::TapHold { tap: code, .. } => {
+                let mods = self.caps_word.process(code);
+                self.temp_register_with(code, mods)
+            }
+            Action::Modded { mods, code } => {
+                let mods = mods | self.caps_word.process(code);
+                self.temp_register_with(code, mods)
+            }
+            Action::ToggleLayer(layer) => self.mapper.toggle_layer(layer),
+            Action::CapsWord => self.caps_word.toggle(),
+            Action::Repeat => {
+                if let Some((code, mods)) = self.last_key {
+                    self.temp_register_with(code, mods)
+                }
+            }
+            Action::AltRepeat => {
+                if let Some((code, mods)) = self.last_key
+                    && let Some(code) = self.repeat.alternate(code)
+                {
+                    self.temp_register_with(code, mods)
+                }
+            }
+            Action::MomentaryLayer(_) | Action::Axis { .. } => {}
+        }
+    }
+
     fn process_event(&mut self, x: usize, y: usize, event: Event) {
         match event {
             Event::Pressed => self.process_key_pressed(x, y),
@@ -127,6 +399,8 @@ where
     }
 
     fn process_key_pressed(&mut self, x: usize, y: usize) {
+        self.flush_auto_shift();
+
         if let Some(action) = self.mapper.get(x as u8, y as u8) {
             if self.register_pressed(x, y, action).is_some() {
                 warn!(
@@ -146,13 +420,81 @@ where
         );
 
         match action {
-            Action::Code(code) => self.handler.register(code),
+            Action::Code(code)
+                if self.auto_shift.applies(code) && !self.has_shortcut_modifiers() =>
+            {
+                self.resolve_pressed(x as usize, y as usize, Action::AutoShift(code))
+            }
+            Action::Code(code) => {
+                self.process_code_pressed(x as usize, y as usize, code, Modifiers::empty())
+            }
+            Action::Modded { mods, code } => {
+                self.process_code_pressed(x as usize, y as usize, code, mods)
+            }
             Action::MomentaryLayer(layer) => self.mapper.activate_layer(layer),
             Action::ToggleLayer(layer) => self.mapper.toggle_layer(layer),
+            Action::CapsWord => self.caps_word.toggle(),
+            Action::Repeat => self.process_repeat_pressed(x as usize, y as usize, false),
+            Action::AltRepeat => self.process_repeat_pressed(x as usize, y as usize, true),
             _ => {}
         }
     }
 
+    fn process_code_pressed(&mut self, x: usize, y: usize, code: KeyCode, mods: Modifiers) {
+        let (code, mut mods, suppressed) = match self.find_key_override(code) {
+            Some(key_override) => {
+                debug!("Key override {} triggered", key_override);
+
+                let suppressed = key_override.suppressed(self.modifiers);
+                self.register_suppressed_modifiers(x, y, suppressed);
+                self.resolve_pressed(x, y, Action::Code(key_override.replacement()));
+
+                (
+                    key_override.replacement(),
+                    key_override.replacement_mods(),
+                    suppressed,
+                )
+            }
+            None => (code, mods, Modifiers::empty()),
+        };
+
+        mods |= self.caps_word.process(code);
+        self.register(code);
+        self.register_weak_modifiers(x, y, mods);
+        self.remember_last_key(code, (self.modifiers & !suppressed) | mods);
+    }
+
+    fn process_repeat_pressed(&mut self, x: usize, y: usize, alternate: bool) {
+        let Some((code, mods)) = self.last_key else {
+            return;
+        };
+
+        let code = if alternate {
+            match self.repeat.alternate(code) {
+                Some(code) => code,
+                None => return,
+            }
+        } else {
+            code
+        };
+
+        self.resolve_pressed(x, y, Action::Code(code));
+        self.register(code);
+        self.register_weak_modifiers(x, y, mods);
+    }
+
+    /// Whether a modifier other than shift is held, so the key pressed is part of a shortcut.
+    fn has_shortcut_modifiers(&self) -> bool {
+        !(self.modifiers - (Modifiers::LEFT_SHIFT | Modifiers::RIGHT_SHIFT)).is_empty()
+    }
+
+    fn find_key_override(&self, code: KeyCode) -> Option<KeyOverride> {
+        self.key_overrides
+            .iter()
+            .find(|x| x.matches(code, self.modifiers))
+            .copied()
+    }
+
     fn process_key_held(&mut self, x: usize, y: usize) {
         let action = {
             let pressed = self.get_pressed_mut(x, y).as_mut().unwrap();
@@ -170,14 +512,27 @@ where
         );
 
         match action {
-            Action::TapHold { hold, .. } => self.handler.register(hold),
+            Action::TapHold { hold, .. } => self.register(hold),
+            Action::AutoShift(code) => {
+                let mods = self.caps_word.process(code) | Modifiers::LEFT_SHIFT;
+                self.temp_register_with(code, mods)
+            }
             _ => {}
         }
     }
 
     fn process_key_released(&mut self, x: usize, y: usize) {
-        if let Some((action, duration, was_tapped)) = self.register_released(x, y) {
-            self.process_action_released(x as u8, y as u8, action, duration, was_tapped)
+        if let Some(pressed) = self.register_released(x, y) {
+            self.handler.remove_weak_modifiers(pressed.weak_modifiers);
+            self.handler
+                .remove_suppressed_modifiers(pressed.suppressed_modifiers);
+            self.process_action_released(
+                x as u8,
+                y as u8,
+                pressed.action,
+                pressed.since.elapsed(),
+                pressed.is_yet_to_be_held,
+            )
         } else {
             warn!(
                 "Key at ({}, {}) was never registered as pressed before being released",
@@ -200,35 +555,135 @@ where
         );
 
         match action {
-            Action::Code(code) => self.handler.unregister(code),
-            Action::TapHold { tap, .. } if was_tapped => self.handler.temp_register(tap),
-            Action::TapHold { hold, .. } => self.handler.unregister(hold),
+            Action::Code(code) | Action::Modded { code, .. } => self.unregister(code),
+            Action::TapHold { tap, .. } if was_tapped => {
+                let mods = self.caps_word.process(tap);
+                self.temp_register_with(tap, mods)
+            }
+            Action::TapHold { hold, .. } => self.unregister(hold),
+            Action::AutoShift(code) if was_tapped => {
+                let mods = self.caps_word.process(code);
+                self.temp_register_with(code, mods)
+            }
             Action::MomentaryLayer(layer) => self.mapper.deactivate_layer(layer),
             _ => {}
         }
     }
 
+    fn register(&mut self, code: KeyCode) {
+        self.clear_weak_modifiers();
+
+        if let Some(mask) = code.modifier_mask() {
+            self.modifiers |= mask;
+        }
+
+        self.handler.register(code);
+    }
+
+    fn unregister(&mut self, code: KeyCode) {
+        if let Some(mask) = code.modifier_mask() {
+            self.modifiers &= !mask;
+        }
+
+        self.handler.unregister(code);
+    }
+
+    fn temp_register_with(&mut self, code: KeyCode, mods: Modifiers) {
+        self.clear_weak_modifiers();
+
+        for m in mods.iter() {
+            self.handler
+                .temp_register(KeyCode::from_modifier_mask(m).unwrap());
+        }
+
+        self.handler.temp_register(code);
+        self.remember_last_key(code, self.modifiers | mods);
+    }
+
+    fn remember_last_key(&mut self, code: KeyCode, mods: Modifiers) {
+        if !code.is_modifier() {
+            self.last_key = Some((code, mods));
+        }
+    }
+
     fn get_event(&mut self, x: usize, y: usize, scan: bool, prev_scan: bool) -> Option<Event> {
         match (scan, prev_scan) {
             (true, false) => Some(Event::Pressed),
             (false, true) => Some(Event::Released),
             (true, true) => self
                 .get_pressed(x, y)
-                .unwrap()
-                .is_ready_to_be_held()
+                .is_some_and(|x| x.is_ready_to_be_held(self.hold_timeout(x.action)))
                 .then_some(Event::Held),
             _ => None,
         }
     }
 
+    fn hold_timeout(&self, action: Action) -> Duration {
+        match action {
+            Action::AutoShift(_) => self.auto_shift.get_timeout(),
+            _ => DEFAULT_TAP_TIMEOUT,
+        }
+    }
+
+    /// Sends any auto-shifted keys that are still waiting for their timeout unshifted, so they
+    /// are not reordered with the key being pressed.
+    fn flush_auto_shift(&mut self) {
+        for y in 0..H {
+            for x in 0..W {
+                let code = match self.get_pressed_mut(x, y) {
+                    Some(pressed) if pressed.is_yet_to_be_held => match pressed.action {
+                        Action::AutoShift(code) => {
+                            pressed.is_yet_to_be_held = false;
+                            code
+                        }
+                        _ => continue,
+                    },
+                    _ => continue,
+                };
+
+                let mods = self.caps_word.process(code);
+                self.temp_register_with(code, mods);
+            }
+        }
+    }
+
     fn register_pressed(&mut self, x: usize, y: usize, action: Action) -> Option<Pressed> {
         self.get_pressed_mut(x, y).replace(Pressed::now(action))
     }
 
-    fn register_released(&mut self, x: usize, y: usize) -> Option<(Action, Duration, bool)> {
-        self.get_pressed_mut(x, y)
-            .take()
-            .map(|x| (x.action, x.since.elapsed(), x.is_yet_to_be_held))
+    fn register_released(&mut self, x: usize, y: usize) -> Option<Pressed> {
+        self.get_pressed_mut(x, y).take()
+    }
+
+    fn register_weak_modifiers(&mut self, x: usize, y: usize, mods: Modifiers) {
+        if let Some(pressed) = self.get_pressed_mut(x, y) {
+            pressed.weak_modifiers |= mods;
+        }
+
+        self.handler.add_weak_modifiers(mods);
+    }
+
+    /// Stops sending the weak modifiers of every key, as they only apply to the key they were
+    /// registered with until another key is registered.
+    fn clear_weak_modifiers(&mut self) {
+        for pressed in self.pressed.iter_mut().flatten().flatten() {
+            self.handler.remove_weak_modifiers(pressed.weak_modifiers);
+            pressed.weak_modifiers = Modifiers::empty();
+        }
+    }
+
+    fn register_suppressed_modifiers(&mut self, x: usize, y: usize, mods: Modifiers) {
+        if let Some(pressed) = self.get_pressed_mut(x, y) {
+            pressed.suppressed_modifiers |= mods;
+        }
+
+        self.handler.add_suppressed_modifiers(mods);
+    }
+
+    fn resolve_pressed(&mut self, x: usize, y: usize, action: Action) {
+        if let Some(pressed) = self.get_pressed_mut(x, y) {
+            pressed.action = action;
+        }
     }
 
     fn get_pressed(&self, x: usize, y: usize) -> Option<Pressed> {
@@ -245,6 +700,8 @@ struct Pressed {
     action: Action,
     since: Instant,
     is_yet_to_be_held: bool,
+    weak_modifiers: Modifiers,
+    suppressed_modifiers: Modifiers,
 }
 
 impl Pressed {
@@ -253,10 +710,250 @@ impl Pressed {
             action,
             since: Instant::now(),
             is_yet_to_be_held: true,
+            weak_modifiers: Modifiers::empty(),
+            suppressed_modifiers: Modifiers::empty(),
+        }
+    }
+
+    fn is_ready_to_be_held(&self, timeout: Duration) -> bool {
+        self.is_yet_to_be_held && self.since.elapsed() >= timeout
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use crate::{
+        interface::mock::{MockHandler, MockReport},
+        qmk_key_codes::*,
+        scan::Composite,
+    };
+
+    use super::*;
+
+    /// Keyboard whose keys are pressed and released by the test instead of scanned.
+    struct Tester<const W: usize, const H: usize, const D: usize> {
+        keyboard: RunningKeyboard<Composite<W, H>, LayeredMap<W, H, D>, MockHandler, W, H, ()>,
+        keys: [[bool; W]; H],
+    }
+
+    impl<const W: usize, const H: usize, const D: usize> Tester<W, H, D> {
+        fn new(map: [[[KeyAction; W]; H]; D], options: Options) -> Self {
+            Self {
+                keyboard: RunningKeyboard::new(
+                    Composite::new(),
+                    LayeredMap::new(map),
+                    MockHandler::new(),
+                    (),
+                    options,
+                ),
+                keys: [[false; W]; H],
+            }
+        }
+
+        fn press(&mut self, x: usize) {
+            self.set(x, true);
+        }
+
+        fn release(&mut self, x: usize) {
+            self.set(x, false);
+        }
+
+        fn tap(&mut self, x: usize) {
+            self.press(x);
+            self.release(x);
+        }
+
+        /// Processes a scan where nothing changed, letting held keys time out.
+        fn idle(&mut self) {
+            let keys = self.keys;
+            self.keyboard.process(&keys, &keys);
         }
+
+        fn set(&mut self, x: usize, pressed: bool) {
+            let prev = self.keys;
+            self.keys[0][x] = pressed;
+            self.keyboard.process(&self.keys, &prev);
+        }
+
+        fn changes(&self) -> Vec<MockReport> {
+            self.keyboard.handler.changes()
+        }
+    }
+
+    fn report(mods: Modifiers, codes: &[KeyCode]) -> MockReport {
+        MockReport::new(mods, codes)
+    }
+
+    #[test]
+    fn caps_word_shifts_only_the_key_it_applies_to() {
+        let mut tester = Tester::new([[[QK_CAPS_WORD_TOGGLE, KC_A, KC_B, KC_1]]], Options::new());
+        let (a, b, one) = (KeyCode::KeyboardA, KeyCode::KeyboardB, KeyCode::Keyboard1);
+
+        tester.tap(0);
+        tester.press(1);
+        tester.press(2);
+        tester.release(1);
+        tester.press(3);
+        tester.release(3);
+        tester.release(2);
+
+        assert_eq!(
+            tester.changes(),
+            [
+                report(Modifiers::empty(), &[]),
+                report(Modifiers::LEFT_SHIFT, &[a]),
+                report(Modifiers::LEFT_SHIFT, &[a, b]),
+                // The shift of the key still held is kept.
+                report(Modifiers::LEFT_SHIFT, &[b]),
+                // Digits are not shifted, even while a shifted letter is held.
+                report(Modifiers::empty(), &[b, one]),
+                report(Modifiers::empty(), &[b]),
+                report(Modifiers::empty(), &[]),
+            ]
+        );
+    }
+
+    #[test]
+    fn shifted_symbol_does_not_shift_other_keys() {
+        let mut tester = Tester::new([[[KC_EXLM, KC_A]]], Options::new());
+        let (one, a) = (KeyCode::Keyboard1, KeyCode::KeyboardA);
+
+        tester.press(0);
+        tester.press(1);
+        tester.release(1);
+        tester.release(0);
+
+        assert_eq!(
+            tester.changes(),
+            [
+                report(Modifiers::LEFT_SHIFT, &[one]),
+                report(Modifiers::empty(), &[one, a]),
+                report(Modifiers::empty(), &[one]),
+                report(Modifiers::empty(), &[]),
+            ]
+        );
     }
 
-    fn is_ready_to_be_held(&self) -> bool {
-        self.is_yet_to_be_held && self.since.elapsed() >= DEFAULT_TAP_TIMEOUT
+    #[test]
+    fn auto_shift_skips_shortcuts() {
+        let mut options = Options::new();
+        options.auto_shift = AutoShiftConfig::new().timeout(Duration::from_millis(5));
+        let mut tester = Tester::new([[[KC_LCTL, KC_C]]], options);
+
+        tester.press(0);
+        tester.press(1);
+        embassy_time::block_for(Duration::from_millis(10));
+        tester.idle();
+        tester.release(1);
+        tester.release(0);
+
+        assert_eq!(
+            tester.changes(),
+            [
+                report(Modifiers::LEFT_CONTROL, &[]),
+                report(Modifiers::LEFT_CONTROL, &[KeyCode::KeyboardC]),
+                report(Modifiers::LEFT_CONTROL, &[]),
+                report(Modifiers::empty(), &[]),
+            ]
+        );
+    }
+
+    #[test]
+    fn key_override_suppresses_its_modifiers_while_held() {
+        static OVERRIDES: [KeyOverride; 1] = [KeyOverride::new(
+            Modifiers::LEFT_SHIFT,
+            KeyCode::Backspace,
+            KeyCode::Delete,
+        )];
+
+        let mut options = Options::new();
+        options.key_overrides = &OVERRIDES;
+        let mut tester = Tester::new([[[KC_LSFT, KC_BSPC]]], options);
+
+        tester.press(0);
+        tester.press(1);
+        tester.release(1);
+        tester.release(0);
+
+        assert_eq!(
+            tester.changes(),
+            [
+                report(Modifiers::LEFT_SHIFT, &[]),
+                report(Modifiers::empty(), &[KeyCode::Delete]),
+                report(Modifiers::LEFT_SHIFT, &[]),
+                report(Modifiers::empty(), &[]),
+            ]
+        );
+    }
+
+    #[test]
+    fn key_override_is_only_checked_when_its_key_is_pressed() {
+        static OVERRIDES: [KeyOverride; 1] = [KeyOverride::new(
+            Modifiers::LEFT_SHIFT,
+            KeyCode::Backspace,
+            KeyCode::Delete,
+        )];
+
+        let mut options = Options::new();
+        options.key_overrides = &OVERRIDES;
+        let mut tester = Tester::new([[[KC_LSFT, KC_BSPC]]], options);
+
+        // Shift pressed after the trigger is sent along with it.
+        tester.press(1);
+        tester.press(0);
+        tester.release(0);
+        tester.release(1);
+
+        // Shift released before the trigger keeps the replacement.
+        tester.press(0);
+        tester.press(1);
+        tester.release(0);
+        tester.release(1);
+
+        assert_eq!(
+            tester.changes(),
+            [
+                report(Modifiers::empty(), &[KeyCode::Backspace]),
+                report(Modifiers::LEFT_SHIFT, &[KeyCode::Backspace]),
+                report(Modifiers::empty(), &[KeyCode::Backspace]),
+                report(Modifiers::empty(), &[]),
+                report(Modifiers::LEFT_SHIFT, &[]),
+                report(Modifiers::empty(), &[KeyCode::Delete]),
+                report(Modifiers::empty(), &[]),
+            ]
+        );
+    }
+
+    #[test]
+    fn repeat_sends_last_key_with_its_modifiers() {
+        let mut tester = Tester::new(
+            [[[KC_LCTL, KC_HOME, QK_REPEAT_KEY, QK_ALT_REPEAT_KEY]]],
+            Options::new(),
+        );
+        let (home, end) = (KeyCode::Home, KeyCode::End);
+
+        tester.press(0);
+        tester.tap(1);
+        tester.release(0);
+        tester.tap(2);
+        tester.tap(3);
+
+        assert_eq!(
+            tester.changes(),
+            [
+                report(Modifiers::LEFT_CONTROL, &[]),
+                report(Modifiers::LEFT_CONTROL, &[home]),
+                report(Modifiers::LEFT_CONTROL, &[]),
+                report(Modifiers::empty(), &[]),
+                report(Modifiers::LEFT_CONTROL, &[home]),
+                report(Modifiers::empty(), &[]),
+                report(Modifiers::LEFT_CONTROL, &[end]),
+                report(Modifiers::empty(), &[]),
+            ]
+        );
     }
 }
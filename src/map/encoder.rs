@@ -0,0 +1,82 @@
+use crate::{Action, encoder::Direction};
+
+use super::Opacity;
+
+/// Action bound to a rotation, or transparent to fall through to the layers below.
+type EncoderAction = Opacity<Option<Action>>;
+
+/// Actions bound to the clockwise and counter-clockwise rotation of each encoder, per layer.
+///
+/// Layers are resolved the same way as in [`LayeredMap`](super::LayeredMap), using its active
+/// layers.
+pub struct EncoderMap<const N: usize, const D: usize> {
+    map: [[[EncoderAction; 2]; N]; D],
+}
+
+impl<const N: usize, const D: usize> EncoderMap<N, D> {
+    const _ASSERT: () = assert!(D >= 1 && D <= 32);
+
+    const DEPTH: u8 = D as u8;
+
+    /// Creates a map from `[clockwise, counter_clockwise]` pairs for each encoder on each layer.
+    pub const fn new(map: [[[EncoderAction; 2]; N]; D]) -> Self {
+        let () = Self::_ASSERT;
+        Self { map }
+    }
+
+    pub fn get(&self, index: u8, direction: Direction, active_layers: u32) -> Option<Action> {
+        let i = match direction {
+            Direction::Clockwise => 0,
+            Direction::CounterClockwise => 1,
+        };
+
+        (0..Self::DEPTH)
+            .rev()
+            .filter(|z| active_layers & (1 << z) != 0)
+            .find_map(|z| self.map[z as usize][index as usize][i].into())
+            .unwrap_or_default()
+    }
+}
+
+impl<const N: usize, const D: usize> From<[[[EncoderAction; 2]; N]; D]> for EncoderMap<N, D> {
+    fn from(map: [[[EncoderAction; 2]; N]; D]) -> Self {
+        Self::new(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{interface::usb::KeyCode, qmk_key_codes::*};
+
+    use super::*;
+
+    #[rustfmt::skip]
+    const TEST_MAP: EncoderMap<1, 2> = EncoderMap::new([
+        [[KC_VLUP, KC_VLDN]],
+        [[KC_PGUP, KC_TRNS]],
+    ]);
+
+    #[test]
+    fn action_for_active_layer() {
+        assert_eq!(
+            TEST_MAP.get(0, Direction::Clockwise, 0b11),
+            Some(Action::Code(KeyCode::PageUp))
+        );
+    }
+
+    #[test]
+    fn action_for_transparent_over_base() {
+        assert_eq!(
+            TEST_MAP.get(0, Direction::CounterClockwise, 0b11),
+            Some(Action::Code(KeyCode::VolumeDown))
+        );
+    }
+
+    #[test]
+    fn action_for_inactive_layer_is_skipped() {
+        assert_eq!(
+            TEST_MAP.get(0, Direction::Clockwise, 0b01),
+            Some(Action::Code(KeyCode::VolumeUp))
+        );
+    }
+}
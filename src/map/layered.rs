@@ -26,6 +26,10 @@ impl<const W: usize, const H: usize, const D: usize> LayeredMap<W, H, D> {
         Self { map, active }
     }
 
+    pub fn active_layers(&self) -> u32 {
+        self.active
+    }
+
     pub fn is_active(&self, layer: u8) -> bool {
         self.active & (1 << layer) != 0
     }
@@ -69,7 +73,7 @@ impl<const W: usize, const H: usize, const D: usize> From<[[[Foo; W]; H]; D]>
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Opacity<T> {
     Opaque(T),
     Transparent,
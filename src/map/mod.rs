@@ -1,7 +1,9 @@
+mod encoder;
 mod layered;
 
 use crate::Action;
 
+pub use encoder::EncoderMap;
 pub use layered::{LayeredMap, Opacity};
 
 pub trait ActionMap<const W: usize, const H: usize> {
@@ -3,10 +3,27 @@
 pub use crate::{
     Keyboard,
     action::Action,
+    auto_shift::AutoShiftConfig,
+    caps_word::CapsWordConfig,
+    encoder::{Direction, Encode, Quadrature},
+    gamepad::{Axis, AxisDirection, Curve, GamepadConfig},
     interface::{
         Handler, Interface,
-        usb::{Config, KeyCode, State, UsbInterface},
+        usb::{Config, KeyCode, Leds, Modifiers, State, UsbInterface},
+    },
+    key_override::KeyOverride,
+    map::{EncoderMap, LayeredMap},
+    repeat::RepeatConfig,
+    scan::{
+        ActuationConfig, Adc, Analog, AnalogConfig, AnalogScan, AntiGhost, BitBangShiftIn,
+        BitBangShiftOut, Calibration, Charlieplex, Col2Row, Composite, Debounce, DebounceAlgorithm,
+        DebounceConfig, DiodeOrientation, Direct, Duplex, Ec, EcSense, Expander, ExpanderChip,
+        FULL_TRAVEL, Hc165Col2Row, Hc595Col2Row, Hc4051, Hc4051Sense, Idle, JapaneseDuplex,
+        MatrixConfig, Polarity, Rotation, Row2Col, Scan, ScanError, ScanErrorPolicy,
+        SharedDebounceConfig, ShiftIn, ShiftOut, SpiShiftIn, SpiShiftOut, Transform,
+    },
+    split::{
+        DetectRole, Half, Handedness, MAX_USER_PAYLOAD, Remote, Role, Secondary, SplitState,
+        SyncState, UsbConfigured, Vbus,
     },
-    map::LayeredMap,
-    scan::{Col2Row, Row2Col, Scan},
 };
@@ -0,0 +1,81 @@
+use crate::interface::usb::KeyCode;
+
+const DEFAULT_ALTERNATES: &[(KeyCode, KeyCode)] = &[
+    (KeyCode::LeftArrow, KeyCode::RightArrow),
+    (KeyCode::UpArrow, KeyCode::DownArrow),
+    (KeyCode::Home, KeyCode::End),
+    (KeyCode::PageUp, KeyCode::PageDown),
+    (KeyCode::LeftBracket, KeyCode::RightBracket),
+    (KeyCode::Keyboard9, KeyCode::Keyboard0),
+    (KeyCode::Comma, KeyCode::Dot),
+    (KeyCode::Undo, KeyCode::Again),
+];
+
+/// Configuration of [`Action::Repeat`](crate::prelude::Action::Repeat) and
+/// [`Action::AltRepeat`](crate::prelude::Action::AltRepeat).
+#[derive(Clone, Copy, Debug)]
+pub struct RepeatConfig {
+    /// Pairs of key codes that are each other's opposite for alternate repeat. The modifiers of
+    /// the last key are kept, so `(Keyboard9, Keyboard0)` turns `(` into `)`. Default: arrows,
+    /// home/end, page up/down, brackets, parentheses, angle brackets and undo/redo.
+    alternates: &'static [(KeyCode, KeyCode)],
+}
+
+impl RepeatConfig {
+    pub const fn new() -> Self {
+        Self {
+            alternates: DEFAULT_ALTERNATES,
+        }
+    }
+
+    pub const fn alternates(mut self, alternates: &'static [(KeyCode, KeyCode)]) -> Self {
+        self.alternates = alternates;
+        self
+    }
+
+    /// The opposite of `code`, if any.
+    pub(crate) fn alternate(&self, code: KeyCode) -> Option<KeyCode> {
+        self.alternates.iter().find_map(|&(a, b)| {
+            if a == code {
+                Some(b)
+            } else if b == code {
+                Some(a)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl Default for RepeatConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alternate_in_both_directions() {
+        let config = RepeatConfig::new();
+        assert_eq!(config.alternate(KeyCode::PageUp), Some(KeyCode::PageDown));
+        assert_eq!(config.alternate(KeyCode::PageDown), Some(KeyCode::PageUp));
+    }
+
+    #[test]
+    fn no_alternate_for_unlisted() {
+        assert_eq!(RepeatConfig::new().alternate(KeyCode::KeyboardA), None);
+    }
+
+    #[test]
+    fn configured_alternates_replace_defaults() {
+        let config = RepeatConfig::new().alternates(&[(KeyCode::KeyboardA, KeyCode::KeyboardB)]);
+        assert_eq!(
+            config.alternate(KeyCode::KeyboardB),
+            Some(KeyCode::KeyboardA)
+        );
+        assert_eq!(config.alternate(KeyCode::PageUp), None);
+    }
+}
@@ -0,0 +1,83 @@
+use super::{super::Polarity, FULL_TRAVEL};
+
+/// Readings of every key at rest and when bottomed out.
+///
+/// [`Analog`](super::Analog) calibrates keys as they are used. The calibration can be saved, for
+/// example to flash, and restored with [`Analog::with_calibration`](super::Analog::with_calibration)
+/// so keys are accurate from the first press.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Calibration<const W: usize, const H: usize> {
+    rest: [[u16; W]; H],
+    bottom: [[u16; W]; H],
+}
+
+impl<const W: usize, const H: usize> Calibration<W, H> {
+    pub const fn new(rest: [[u16; W]; H], bottom: [[u16; W]; H]) -> Self {
+        Self { rest, bottom }
+    }
+
+    pub const fn rest(&self) -> &[[u16; W]; H] {
+        &self.rest
+    }
+
+    pub const fn bottom(&self) -> &[[u16; W]; H] {
+        &self.bottom
+    }
+
+    /// Calibration taking the first readings as rest, with every key expected to travel at
+    /// least `range` to bottom out.
+    pub(super) fn from_rest(rest: &[[u16; W]; H], polarity: Polarity, range: u16) -> Self {
+        Self {
+            rest: *rest,
+            bottom: rest.map(|row| row.map(|rest| advance(rest, range, polarity))),
+        }
+    }
+
+    /// Widens the calibration to include `readings`.
+    pub(super) fn update(&mut self, readings: &[[u16; W]; H], polarity: Polarity, range: u16) {
+        for (y, row) in readings.iter().enumerate() {
+            for (x, &reading) in row.iter().enumerate() {
+                let rest = &mut self.rest[y][x];
+                if distance(reading, *rest, polarity) > 0 {
+                    *rest = reading;
+                }
+
+                let bottom = &mut self.bottom[y][x];
+                if distance(*bottom, reading, polarity) > 0 {
+                    *bottom = reading;
+                }
+                if distance(*rest, *bottom, polarity) < range {
+                    *bottom = advance(*rest, range, polarity);
+                }
+            }
+        }
+    }
+
+    /// Travel of the key at `x`, `y` giving `reading`, from 0 at rest to [`FULL_TRAVEL`].
+    pub(super) fn travel(&self, x: usize, y: usize, reading: u16, polarity: Polarity) -> u8 {
+        let span = distance(self.rest[y][x], self.bottom[y][x], polarity) as u32;
+        let travel = distance(self.rest[y][x], reading, polarity) as u32;
+
+        match span {
+            0 if travel > 0 => FULL_TRAVEL,
+            0 => 0,
+            _ => (travel * FULL_TRAVEL as u32 / span).min(FULL_TRAVEL as u32) as u8,
+        }
+    }
+}
+
+/// How far `to` is pressed beyond `from`, or 0 if it is not.
+fn distance(from: u16, to: u16, polarity: Polarity) -> u16 {
+    match polarity {
+        Polarity::ActiveHigh => to.saturating_sub(from),
+        Polarity::ActiveLow => from.saturating_sub(to),
+    }
+}
+
+/// Reading pressed `by` beyond `from`.
+fn advance(from: u16, by: u16, polarity: Polarity) -> u16 {
+    match polarity {
+        Polarity::ActiveHigh => from.saturating_add(by),
+        Polarity::ActiveLow => from.saturating_sub(by),
+    }
+}
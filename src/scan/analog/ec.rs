@@ -0,0 +1,229 @@
+use embassy_time::{Duration, Timer};
+use embedded_hal::digital::OutputPin;
+
+use super::{
+    super::ScanError,
+    Adc, AnalogScan,
+    hc4051::{DEFAULT_MUX_SETTLE, select_channel},
+};
+
+pub const DEFAULT_EC_DISCHARGE: Duration = Duration::from_micros(10);
+
+/// Reads the sense lines of the rows of an [`Ec`] matrix.
+pub trait EcSense {
+    /// Connects the sense line of `row` to the ADC or comparator.
+    fn select(&mut self, row: usize) -> impl core::future::Future<Output = Result<(), ScanError>>;
+
+    /// Reads the charge on the selected sense line. A comparator can be read as 0 or
+    /// [`u16::MAX`].
+    fn read(&mut self) -> impl core::future::Future<Output = Result<u16, ScanError>>;
+}
+
+/// [`EcSense`] reading up to 8 rows through a 74HC4051 multiplexer into an ADC.
+pub struct Hc4051Sense<S, A> {
+    select: [S; 3],
+    adc: A,
+    /// Time between selecting a row and reading it. Default: 10 µs.
+    settle: Duration,
+}
+
+impl<S: OutputPin, A: Adc> Hc4051Sense<S, A> {
+    pub const fn new(select: [S; 3], adc: A) -> Self {
+        Self {
+            select,
+            adc,
+            settle: DEFAULT_MUX_SETTLE,
+        }
+    }
+
+    pub const fn settle(mut self, settle: Duration) -> Self {
+        self.settle = settle;
+        self
+    }
+}
+
+impl<S: OutputPin, A: Adc> EcSense for Hc4051Sense<S, A> {
+    async fn select(&mut self, row: usize) -> Result<(), ScanError> {
+        select_channel(&mut self.select, row)?;
+        Timer::after(self.settle).await;
+        Ok(())
+    }
+
+    async fn read(&mut self) -> Result<u16, ScanError> {
+        self.adc.read().await
+    }
+}
+
+/// Reads electro-capacitive (Topre-style) keys, whose capacitance rises as they are pressed.
+///
+/// Every key sits between a column drive line and a row sense line. To read a key, the discharge
+/// pin is set high to stop draining the sense lines, its column is driven high to charge the key
+/// and the sense line of its row is read right away. The column and discharge pin are then set
+/// low, draining the sense lines before the next key.
+///
+/// The readings are in arbitrary units, so wrap this in an [`Analog`](super::Analog) scanner to
+/// calibrate them and set the threshold of every key. EC sensors change by only a fraction of
+/// their range, so the [`AnalogConfig::range`](super::AnalogConfig::range) should be lowered to
+/// match.
+pub struct Ec<C, D, R, const W: usize, const H: usize> {
+    cols: [C; W],
+    discharge: D,
+    rows: R,
+    /// Time the sense lines are drained for after reading a key. Default: 10 µs.
+    discharge_time: Duration,
+}
+
+impl<C, D, R, const W: usize, const H: usize> Ec<C, D, R, W, H>
+where
+    C: OutputPin,
+    D: OutputPin,
+    R: EcSense,
+{
+    pub const fn new(cols: [C; W], discharge: D, rows: R) -> Self {
+        Self {
+            cols,
+            discharge,
+            rows,
+            discharge_time: DEFAULT_EC_DISCHARGE,
+        }
+    }
+
+    pub const fn discharge_time(mut self, time: Duration) -> Self {
+        self.discharge_time = time;
+        self
+    }
+
+    async fn read_key(&mut self, x: usize) -> Result<u16, ScanError> {
+        self.discharge.set_high().map_err(ScanError::pin)?;
+        self.cols[x].set_high().map_err(ScanError::pin)?;
+        let reading = self.rows.read().await;
+
+        self.cols[x].set_low().map_err(ScanError::pin)?;
+        self.discharge.set_low().map_err(ScanError::pin)?;
+        Timer::after(self.discharge_time).await;
+        reading
+    }
+}
+
+impl<C, D, R, const W: usize, const H: usize> AnalogScan<W, H> for Ec<C, D, R, W, H>
+where
+    C: OutputPin,
+    D: OutputPin,
+    R: EcSense,
+{
+    type Error = ScanError;
+
+    async fn read(&mut self, buf: &mut [[u16; W]; H]) -> Result<(), ScanError> {
+        // Lines may start out driven, or be left driven by a failed scan.
+        for pin in self.cols.iter_mut() {
+            pin.set_low().map_err(ScanError::pin)?;
+        }
+        self.discharge.set_low().map_err(ScanError::pin)?;
+
+        for (y, row) in buf.iter_mut().enumerate() {
+            self.rows.select(y).await?;
+
+            for (x, reading) in row.iter_mut().enumerate() {
+                *reading = self.read_key(x).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use embassy_futures::block_on;
+
+    use crate::scan::{
+        Scan,
+        analog::{ActuationConfig, Analog, AnalogConfig, Calibration},
+        mock::MockOutput,
+    };
+
+    use super::*;
+
+    /// Simulated capacitance of every key, read on the selected row while its column is driven
+    /// and the sense lines are not being drained.
+    struct SimulatedSense<'a, const W: usize, const H: usize> {
+        capacitance: [[u16; W]; H],
+        row: usize,
+        cols: &'a [Cell<bool>; W],
+        discharging: &'a Cell<bool>,
+    }
+
+    impl<const W: usize, const H: usize> EcSense for SimulatedSense<'_, W, H> {
+        async fn select(&mut self, row: usize) -> Result<(), ScanError> {
+            self.row = row;
+            Ok(())
+        }
+
+        async fn read(&mut self) -> Result<u16, ScanError> {
+            let mut driven = (0..W).filter(|&x| self.cols[x].get());
+            Ok(
+                match (self.discharging.get(), driven.next(), driven.next()) {
+                    (false, Some(x), None) => self.capacitance[self.row][x],
+                    _ => 0,
+                },
+            )
+        }
+    }
+
+    fn scan<const W: usize, const H: usize>(
+        capacitance: [[u16; W]; H],
+        calibration: Calibration<W, H>,
+        thresholds: &[(usize, usize, u8)],
+    ) -> [[bool; W]; H] {
+        let cols = [(); W].map(|_| Cell::new(true));
+        let discharging = Cell::new(false);
+        let sense = SimulatedSense {
+            capacitance,
+            row: 0,
+            cols: &cols,
+            discharging: &discharging,
+        };
+        let drive = core::array::from_fn(|x| {
+            let col = &cols[x];
+            MockOutput::new(move |high| col.set(high))
+        });
+        let discharge = MockOutput::new(|high| discharging.set(!high));
+
+        let ec = Ec::<_, _, _, W, H>::new(drive, discharge, sense);
+        let config = AnalogConfig::new().range(100);
+        let mut scanner = Analog::new(ec, config).with_calibration(calibration);
+        for &(x, y, actuation) in thresholds {
+            let key = ActuationConfig::new()
+                .actuation(actuation)
+                .release(actuation);
+            scanner.set_actuation(x, y, key);
+        }
+
+        let mut buf = [[false; W]; H];
+        block_on(scanner.scan(&mut buf)).unwrap();
+        assert!(discharging.get());
+        assert!(cols.iter().all(|col| !col.get()));
+        buf
+    }
+
+    #[test]
+    fn reads_every_key() {
+        let capacitance = [[100, 180, 140], [110, 100, 200]];
+        let calibration = Calibration::new([[100; 3]; 2], [[200; 3]; 2]);
+        let buf = scan(capacitance, calibration, &[]);
+
+        assert_eq!(buf, [[false, true, false], [false, false, true]]);
+    }
+
+    #[test]
+    fn per_key_thresholds() {
+        let capacitance = [[140, 140, 140]];
+        let calibration = Calibration::new([[100; 3]], [[200; 3]]);
+        let thresholds = [(0, 0, 64), (2, 0, 192)];
+        let buf = scan(capacitance, calibration, &thresholds);
+
+        assert_eq!(buf, [[true, false, false]]);
+    }
+}
@@ -0,0 +1,120 @@
+use embassy_time::{Duration, Timer};
+use embedded_hal::digital::OutputPin;
+
+use super::{super::ScanError, Adc, AnalogScan};
+
+pub const DEFAULT_MUX_SETTLE: Duration = Duration::from_micros(10);
+
+/// Reads analog keys through 74HC4051 multiplexers, each connecting one of 8 sensors to an ADC
+/// input.
+///
+/// Every multiplexer shares the three select pins, `S0` first. Key `i`, counting row by row, is
+/// on channel `i % 8` of multiplexer `i / 8`.
+pub struct Hc4051<S, A, const M: usize, const W: usize, const H: usize> {
+    select: [S; 3],
+    adcs: [A; M],
+    /// Time between selecting a channel and reading it. Default: 10 µs.
+    settle: Duration,
+}
+
+impl<S, A, const M: usize, const W: usize, const H: usize> Hc4051<S, A, M, W, H>
+where
+    S: OutputPin,
+    A: Adc,
+{
+    const _ASSERT: () = assert!(W * H <= 8 * M);
+
+    pub const fn new(select: [S; 3], adcs: [A; M]) -> Self {
+        let () = Self::_ASSERT;
+        Self {
+            select,
+            adcs,
+            settle: DEFAULT_MUX_SETTLE,
+        }
+    }
+
+    pub const fn settle(mut self, settle: Duration) -> Self {
+        self.settle = settle;
+        self
+    }
+}
+
+impl<S, A, const M: usize, const W: usize, const H: usize> AnalogScan<W, H>
+    for Hc4051<S, A, M, W, H>
+where
+    S: OutputPin,
+    A: Adc,
+{
+    type Error = ScanError;
+
+    async fn read(&mut self, buf: &mut [[u16; W]; H]) -> Result<(), ScanError> {
+        for channel in 0..8 {
+            select_channel(&mut self.select, channel)?;
+            Timer::after(self.settle).await;
+
+            for (mux, adc) in self.adcs.iter_mut().enumerate() {
+                let key = mux * 8 + channel;
+                if key < W * H {
+                    buf[key / W][key % W] = adc.read().await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Connects `channel` of multiplexers sharing the `select` pins to their outputs.
+pub(super) fn select_channel<S: OutputPin>(
+    select: &mut [S; 3],
+    channel: usize,
+) -> Result<(), ScanError> {
+    for (bit, pin) in select.iter_mut().enumerate() {
+        pin.set_state((channel >> bit & 1 == 1).into())
+            .map_err(ScanError::pin)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use embassy_futures::block_on;
+
+    use crate::scan::mock::MockOutput;
+
+    use super::*;
+
+    struct MockAdc<'a> {
+        mux: u16,
+        channel: &'a Cell<u16>,
+    }
+
+    impl Adc for MockAdc<'_> {
+        async fn read(&mut self) -> Result<u16, ScanError> {
+            Ok(self.mux * 100 + self.channel.get())
+        }
+    }
+
+    #[test]
+    fn reads_every_channel() {
+        let channel = Cell::new(0);
+        let select = [0, 1, 2].map(|bit| {
+            let channel = &channel;
+            MockOutput::new(move |high| {
+                channel.set(channel.get() & !(1 << bit) | (high as u16) << bit)
+            })
+        });
+        let adcs = [0, 1].map(|mux| MockAdc {
+            mux,
+            channel: &channel,
+        });
+
+        let mut scanner = Hc4051::new(select, adcs);
+        let mut buf = [[0; 5]; 2];
+        block_on(scanner.read(&mut buf)).unwrap();
+
+        assert_eq!(buf, [[0, 1, 2, 3, 4], [5, 6, 7, 100, 101]]);
+    }
+}
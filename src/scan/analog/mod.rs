@@ -0,0 +1,361 @@
+//! Scanning of analog keys, like Hall-effect switches, whose sensors report how far they are
+//! pressed rather than whether they are.
+
+mod calibration;
+mod ec;
+mod hc4051;
+
+use super::{Polarity, Scan, ScanError};
+
+pub use calibration::Calibration;
+pub use ec::{Ec, EcSense, Hc4051Sense};
+pub use hc4051::Hc4051;
+
+/// Travel of a bottomed out key. Travel goes from 0 at rest to this.
+pub const FULL_TRAVEL: u8 = u8::MAX;
+
+/// Reads one analog channel, like an ADC input.
+pub trait Adc {
+    /// Reads the current value of the channel. Values of different ADCs need not share a range,
+    /// as every key is calibrated separately.
+    fn read(&mut self) -> impl core::future::Future<Output = Result<u16, ScanError>>;
+}
+
+/// Reads the sensor of every key of a matrix.
+pub trait AnalogScan<const W: usize, const H: usize> {
+    type Error: core::fmt::Debug;
+
+    fn read(
+        &mut self,
+        buf: &mut [[u16; W]; H],
+    ) -> impl core::future::Future<Output = Result<(), Self::Error>>;
+}
+
+/// Where a key actuates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ActuationConfig {
+    /// Travel at which a released key is pressed. Default: half of [`FULL_TRAVEL`].
+    actuation: u8,
+    /// Travel below which a pressed key is released. Must be at most `actuation`, the difference
+    /// giving hysteresis against noise. Default: 3/8 of [`FULL_TRAVEL`].
+    release: u8,
+    /// Travel a key must move up to be released, or down to be pressed again, once it has been
+    /// pressed and until it goes back above the release point. Default: `None`, for fixed
+    /// actuation and release points.
+    rapid_trigger: Option<u8>,
+}
+
+impl ActuationConfig {
+    pub const fn new() -> Self {
+        Self {
+            actuation: FULL_TRAVEL / 2,
+            release: FULL_TRAVEL / 8 * 3,
+            rapid_trigger: None,
+        }
+    }
+
+    pub const fn actuation(mut self, travel: u8) -> Self {
+        self.actuation = travel;
+        self
+    }
+
+    pub const fn release(mut self, travel: u8) -> Self {
+        self.release = travel;
+        self
+    }
+
+    /// Enables rapid trigger, releasing and pressing keys again as soon as they change direction
+    /// by `distance`.
+    pub const fn rapid_trigger(mut self, distance: u8) -> Self {
+        self.rapid_trigger = Some(distance);
+        self
+    }
+}
+
+/// State of an analog key.
+#[derive(Clone, Copy, Default)]
+struct KeyState {
+    pressed: bool,
+    /// Deepest travel since the key was pressed, or shallowest since it was released, while
+    /// rapid trigger applies.
+    extreme: Option<u8>,
+}
+
+impl KeyState {
+    fn update(&mut self, config: &ActuationConfig, travel: u8) {
+        if travel < config.release {
+            *self = Self::default();
+            return;
+        }
+
+        match (config.rapid_trigger, self.extreme) {
+            (Some(distance), Some(extreme)) => {
+                let moved = if self.pressed {
+                    extreme.saturating_sub(travel)
+                } else {
+                    travel.saturating_sub(extreme)
+                };
+
+                if moved >= distance {
+                    self.pressed = !self.pressed;
+                    self.extreme = Some(travel);
+                } else if self.pressed == (travel > extreme) {
+                    self.extreme = Some(travel);
+                }
+            }
+            _ if !self.pressed && travel >= config.actuation => {
+                self.pressed = true;
+                self.extreme = config.rapid_trigger.map(|_| travel);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Default for ActuationConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Configuration of an [`Analog`] scanner.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AnalogConfig {
+    /// Whether readings rise or fall as keys are pressed. Default: [`Polarity::ActiveHigh`].
+    polarity: Polarity,
+    /// Least difference between the readings of a key at rest and bottomed out, assumed until a
+    /// key is seen travelling further. Keeps keys that have not been fully pressed yet from
+    /// actuating on noise. Default: 256.
+    range: u16,
+    /// Default: [`ActuationConfig::new`].
+    actuation: ActuationConfig,
+}
+
+impl AnalogConfig {
+    pub const fn new() -> Self {
+        Self {
+            polarity: Polarity::ActiveHigh,
+            range: 256,
+            actuation: ActuationConfig::new(),
+        }
+    }
+
+    pub const fn polarity(mut self, polarity: Polarity) -> Self {
+        self.polarity = polarity;
+        self
+    }
+
+    pub const fn range(mut self, range: u16) -> Self {
+        self.range = range;
+        self
+    }
+
+    /// Sets where every key actuates.
+    pub const fn actuation(mut self, actuation: ActuationConfig) -> Self {
+        self.actuation = actuation;
+        self
+    }
+}
+
+impl Default for AnalogConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Scanner turning the readings of analog keys into key states.
+///
+/// Keys are calibrated automatically: the reading of a key at rest is the least pressed one seen,
+/// starting with the first scan, so keys must not be held while the keyboard starts unless a
+/// saved [`Calibration`] is restored. Bottom-out readings are the most pressed ones seen.
+pub struct Analog<S, const W: usize, const H: usize> {
+    scanner: S,
+    config: AnalogConfig,
+    keys: [[ActuationConfig; W]; H],
+    calibration: Option<Calibration<W, H>>,
+    travel: [[u8; W]; H],
+    states: [[KeyState; W]; H],
+}
+
+impl<S: AnalogScan<W, H>, const W: usize, const H: usize> Analog<S, W, H> {
+    pub const fn new(scanner: S, config: AnalogConfig) -> Self {
+        Self {
+            scanner,
+            config,
+            keys: [[config.actuation; W]; H],
+            calibration: None,
+            travel: [[0; W]; H],
+            states: [[KeyState {
+                pressed: false,
+                extreme: None,
+            }; W]; H],
+        }
+    }
+
+    /// Starts from a saved calibration instead of the first scan.
+    pub const fn with_calibration(mut self, calibration: Calibration<W, H>) -> Self {
+        self.calibration = Some(calibration);
+        self
+    }
+
+    /// Current calibration, once the first scan has been made.
+    pub fn calibration(&self) -> Option<&Calibration<W, H>> {
+        self.calibration.as_ref()
+    }
+
+    pub fn actuation(&self, x: usize, y: usize) -> ActuationConfig {
+        self.keys[y][x]
+    }
+
+    pub fn set_actuation(&mut self, x: usize, y: usize, config: ActuationConfig) {
+        self.keys[y][x] = config;
+    }
+
+    /// Reads the travel of every key, from 0 at rest to [`FULL_TRAVEL`].
+    pub async fn read_travel(&mut self, buf: &mut [[u8; W]; H]) -> Result<(), S::Error> {
+        let mut readings = [[0; W]; H];
+        self.scanner.read(&mut readings).await?;
+
+        let AnalogConfig {
+            polarity, range, ..
+        } = self.config;
+        let calibration = self
+            .calibration
+            .get_or_insert_with(|| Calibration::from_rest(&readings, polarity, range));
+        calibration.update(&readings, polarity, range);
+
+        for (y, row) in readings.iter().enumerate() {
+            for (x, &reading) in row.iter().enumerate() {
+                buf[y][x] = calibration.travel(x, y, reading, polarity);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S: AnalogScan<W, H>, const W: usize, const H: usize> Scan<W, H> for Analog<S, W, H> {
+    type Error = S::Error;
+
+    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> Result<(), S::Error> {
+        let mut travel = [[0; W]; H];
+        self.read_travel(&mut travel).await?;
+        self.travel = travel;
+
+        for (y, row) in travel.iter().enumerate() {
+            for (x, &travel) in row.iter().enumerate() {
+                self.states[y][x].update(&self.keys[y][x], travel);
+            }
+        }
+
+        *buf = self.states.map(|row| row.map(|state| state.pressed));
+        Ok(())
+    }
+
+    fn travel(&self, x: usize, y: usize) -> Option<u8> {
+        Some(self.travel[y][x])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+
+    use embassy_futures::block_on;
+
+    use super::*;
+
+    /// Synthetic ADC trace, giving one set of readings per scan.
+    struct Trace<'a, const W: usize, const H: usize>(&'a [[[u16; W]; H]]);
+
+    impl<const W: usize, const H: usize> AnalogScan<W, H> for Trace<'_, W, H> {
+        type Error = Infallible;
+
+        async fn read(&mut self, buf: &mut [[u16; W]; H]) -> Result<(), Infallible> {
+            let (first, rest) = self.0.split_first().unwrap();
+            *buf = *first;
+            self.0 = rest;
+            Ok(())
+        }
+    }
+
+    fn scan<const W: usize, const H: usize>(scanner: &mut impl Scan<W, H>) -> [[bool; W]; H] {
+        let mut buf = [[false; W]; H];
+        block_on(scanner.scan(&mut buf)).unwrap();
+        buf
+    }
+
+    #[test]
+    fn calibrates_rest_and_bottom() {
+        let trace = [
+            [[1000, 2000]],
+            [[990, 2100]],
+            [[1800, 2000]],
+            [[1400, 2000]],
+        ];
+        let mut scanner = Analog::new(Trace(&trace), AnalogConfig::new());
+        let mut buf = [[0; 2]; 1];
+        for _ in 0..3 {
+            block_on(scanner.read_travel(&mut buf)).unwrap();
+        }
+
+        let calibration = scanner.calibration().unwrap();
+        assert_eq!(calibration.rest(), &[[990, 2000]]);
+        assert_eq!(calibration.bottom(), &[[1800, 2256]]);
+
+        block_on(scanner.read_travel(&mut buf)).unwrap();
+        assert_eq!(buf, [[129, 0]]);
+    }
+
+    #[test]
+    fn actuates_with_hysteresis() {
+        let config = AnalogConfig::new()
+            .range(100)
+            .actuation(ActuationConfig::new().actuation(128).release(64));
+        // Travel 0, 127, 130, 102, 66, 38 and 130 of 255.
+        let trace = [500, 550, 551, 540, 526, 515, 551].map(|reading| [[reading]]);
+        let mut scanner = Analog::new(Trace(&trace), config);
+
+        let states = [(); 7].map(|_| scan(&mut scanner)[0][0]);
+        assert_eq!(states, [false, false, true, true, true, false, true]);
+    }
+
+    #[test]
+    fn rapid_trigger_per_key() {
+        let fixed = ActuationConfig::new().actuation(128).release(64);
+        let config = AnalogConfig::new().range(255).actuation(fixed);
+        // Travel equals the readings, for both keys.
+        let trace = [0, 128, 200, 185, 180, 195, 200, 100, 50, 100].map(|r| [[r, r]]);
+        let mut scanner = Analog::new(Trace(&trace), config);
+        scanner.set_actuation(0, 0, fixed.rapid_trigger(20));
+
+        let states = [(); 10].map(|_| scan(&mut scanner)[0]);
+        let rapid = states.map(|[rapid, _]| rapid);
+        let fixed = states.map(|[_, fixed]| fixed);
+        assert_eq!(
+            rapid,
+            [
+                false, true, true, true, false, false, true, false, false, false
+            ]
+        );
+        assert_eq!(
+            fixed,
+            [
+                false, true, true, true, true, true, true, true, false, false
+            ]
+        );
+    }
+
+    #[test]
+    fn falling_readings_and_saved_calibration() {
+        let calibration = Calibration::new([[3000]], [[1000]]);
+        let config = AnalogConfig::new().polarity(Polarity::ActiveLow);
+        let trace = [[[2500]], [[1900]], [[2300]]];
+        let mut scanner = Analog::new(Trace(&trace), config).with_calibration(calibration);
+
+        let states = [(); 3].map(|_| scan(&mut scanner)[0][0]);
+        assert_eq!(states, [false, true, false]);
+        assert_eq!(scanner.calibration(), Some(&calibration));
+    }
+}
@@ -0,0 +1,121 @@
+use super::{Debounce, Scan, debounce::debounce};
+
+/// Blocks ghost keys on matrices without diodes.
+///
+/// When keys at three corners of a rectangle are pressed, the fourth reads as pressed too, so
+/// every key at the corners of a pressed rectangle is ambiguous. Ambiguous keys keep their state
+/// from the previous scan, while every other key is passed through.
+pub struct AntiGhost<S, const W: usize, const H: usize> {
+    scanner: S,
+    states: [[bool; W]; H],
+}
+
+impl<S, const W: usize, const H: usize> AntiGhost<S, W, H> {
+    pub const fn new(scanner: S) -> Self {
+        Self {
+            scanner,
+            states: [[false; W]; H],
+        }
+    }
+
+    pub const fn debounced(self) -> Debounce<Self, W, H>
+    where
+        S: Scan<W, H>,
+    {
+        debounce(self)
+    }
+}
+
+impl<S: Scan<W, H>, const W: usize, const H: usize> Scan<W, H> for AntiGhost<S, W, H> {
+    type Error = S::Error;
+
+    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> Result<(), S::Error> {
+        self.scanner.scan(buf).await?;
+
+        let ambiguous = ambiguous(buf);
+        for y in 0..H {
+            for x in 0..W {
+                if ambiguous[y][x] {
+                    buf[y][x] = self.states[y][x];
+                }
+            }
+        }
+
+        self.states = *buf;
+        Ok(())
+    }
+
+    async fn wait_for_press(&mut self) -> bool {
+        self.scanner.wait_for_press().await
+    }
+
+    fn travel(&self, x: usize, y: usize) -> Option<u8> {
+        self.scanner.travel(x, y)
+    }
+}
+
+/// Keys at the corners of rectangles whose every corner is pressed.
+fn ambiguous<const W: usize, const H: usize>(buf: &[[bool; W]; H]) -> [[bool; W]; H] {
+    let mut ambiguous = [[false; W]; H];
+
+    for y1 in 0..H {
+        for y2 in y1 + 1..H {
+            let shared = (0..W).filter(|&x| buf[y1][x] && buf[y2][x]);
+            if shared.clone().count() < 2 {
+                continue;
+            }
+
+            for x in shared {
+                ambiguous[y1][x] = true;
+                ambiguous[y2][x] = true;
+            }
+        }
+    }
+
+    ambiguous
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use crate::scan::mock::{MockMatrix, scan};
+
+    use super::*;
+
+    #[test]
+    fn passes_unambiguous_keys() {
+        let keys = Cell::new([[true, true, false], [true, false, false]]);
+        let mut scanner = AntiGhost::new(MockMatrix::new(&keys));
+
+        assert_eq!(
+            scan(&mut scanner),
+            [[true, true, false], [true, false, false]]
+        );
+    }
+
+    #[test]
+    fn blocks_ghost_key() {
+        let keys = Cell::new([[true, true, false], [true, false, false]]);
+        let mut scanner = AntiGhost::new(MockMatrix::new(&keys));
+        scan(&mut scanner);
+
+        // Pressing the key at the fourth corner is indistinguishable from its ghost.
+        keys.set([[true, true, false], [true, true, true]]);
+
+        assert_eq!(
+            scan(&mut scanner),
+            [[true, true, false], [true, false, true]]
+        );
+    }
+
+    #[test]
+    fn releases_ambiguous_keys_once_resolved() {
+        let keys = Cell::new([[true, true], [true, true]]);
+        let mut scanner = AntiGhost::new(MockMatrix::new(&keys));
+        assert_eq!(scan(&mut scanner), [[false; 2]; 2]);
+
+        keys.set([[false, true], [false, true]]);
+        assert_eq!(scan(&mut scanner), [[false, true], [false, true]]);
+    }
+}
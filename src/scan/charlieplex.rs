@@ -0,0 +1,138 @@
+use embassy_time::{Duration, Timer};
+use embedded_hal::digital::{InputPin, OutputPin};
+
+use super::{Debounce, Scan, ScanError, debounce::debounce};
+
+pub const DEFAULT_CHARLIEPLEX_SETTLE: Duration = Duration::from_micros(30);
+
+/// Which side of the diodes the rows of a [`Charlieplex`] matrix are on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DiodeOrientation {
+    /// Row `y` holds the keys whose diode points towards pin `y`.
+    RowsAtCathode,
+    /// Row `y` holds the keys whose diode points away from pin `y`.
+    RowsAtAnode,
+}
+
+/// Scanner for charlieplexed matrices, where a key with a diode sits between every ordered pair
+/// of pins, giving `H * (H - 1)` keys for `H` pins.
+///
+/// Pins must be bidirectional, like open-drain outputs with pull-ups, so that setting them high
+/// releases the line and lets it be read. Each pin is driven low in turn while the others are
+/// read.
+///
+/// Each row holds the keys of one pin, with the other pins as columns in order, skipping the pin
+/// itself.
+pub struct Charlieplex<P, const W: usize, const H: usize> {
+    pins: [P; H],
+    /// Time between driving a pin and reading the others. Default: 30 µs.
+    settle: Duration,
+    /// Which side of the diodes rows are on. Default: [`DiodeOrientation::RowsAtCathode`].
+    orientation: DiodeOrientation,
+}
+
+impl<P, const W: usize, const H: usize> Charlieplex<P, W, H>
+where
+    P: InputPin + OutputPin,
+{
+    const _ASSERT: () = assert!(W + 1 == H);
+
+    pub const fn new(pins: [P; H]) -> Self {
+        let () = Self::_ASSERT;
+        Self {
+            pins,
+            settle: DEFAULT_CHARLIEPLEX_SETTLE,
+            orientation: DiodeOrientation::RowsAtCathode,
+        }
+    }
+
+    pub const fn settle(mut self, settle: Duration) -> Self {
+        self.settle = settle;
+        self
+    }
+
+    pub const fn orientation(mut self, orientation: DiodeOrientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    pub const fn debounced(self) -> Debounce<Self, W, H> {
+        debounce(self)
+    }
+}
+
+impl<P, const W: usize, const H: usize> Scan<W, H> for Charlieplex<P, W, H>
+where
+    P: InputPin + OutputPin,
+{
+    type Error = ScanError;
+
+    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> Result<(), ScanError> {
+        // Pins may start out driven, or be left driven by a failed scan.
+        for pin in self.pins.iter_mut() {
+            pin.set_high().map_err(ScanError::pin)?;
+        }
+
+        for cathode in 0..H {
+            self.pins[cathode].set_low().map_err(ScanError::pin)?;
+            Timer::after(self.settle).await;
+
+            for anode in (0..H).filter(|&anode| anode != cathode) {
+                let pressed = self.pins[anode].is_low().map_err(ScanError::pin)?;
+
+                let (x, y) = match self.orientation {
+                    DiodeOrientation::RowsAtCathode => (column(anode, cathode), cathode),
+                    DiodeOrientation::RowsAtAnode => (column(cathode, anode), anode),
+                };
+                buf[y][x] = pressed;
+            }
+
+            self.pins[cathode].set_high().map_err(ScanError::pin)?;
+            Timer::after(self.settle).await;
+        }
+
+        Ok(())
+    }
+}
+
+/// Column of `pin` in the row of pin `row`, which skips the row's own pin.
+const fn column(pin: usize, row: usize) -> usize {
+    if pin < row { pin } else { pin - 1 }
+}
+
+#[cfg(test)]
+mod tests {
+    use embassy_futures::block_on;
+
+    use crate::scan::mock::MockLines;
+
+    use super::*;
+
+    fn lines() -> MockLines<3> {
+        let lines = MockLines::new();
+        lines.press(0, 2);
+        lines.press(2, 1);
+        lines
+    }
+
+    #[test]
+    fn rows_at_cathode() {
+        let lines = lines();
+        let mut buf = [[false; 2]; 3];
+        block_on(Charlieplex::new(lines.pins()).scan(&mut buf)).unwrap();
+
+        assert_eq!(buf, [[false, false], [false, true], [true, false]]);
+        assert!(lines.all_released());
+    }
+
+    #[test]
+    fn rows_at_anode() {
+        let lines = lines();
+        let mut scanner = Charlieplex::new(lines.pins()).orientation(DiodeOrientation::RowsAtAnode);
+        let mut buf = [[false; 2]; 3];
+        block_on(scanner.scan(&mut buf)).unwrap();
+
+        assert_eq!(buf, [[false, true], [false, false], [false, true]]);
+    }
+}
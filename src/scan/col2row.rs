@@ -1,45 +1,162 @@
-use core::convert::Infallible;
-
-use embassy_time::Timer;
+use embassy_futures::select::select_array;
 use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal_async::digital::Wait;
 
-use super::{Scan, debounce::debounce};
+use super::{Debounce, Idle, MatrixConfig, Scan, ScanError, debounce::debounce};
 
 pub struct Col2Row<I, O, const W: usize, const H: usize> {
     cols: [O; W],
     rows: [I; H],
+    config: MatrixConfig,
 }
 
 impl<I, O, const W: usize, const H: usize> Col2Row<I, O, W, H>
 where
-    I: InputPin<Error = Infallible>,
-    O: OutputPin<Error = Infallible>,
+    I: InputPin,
+    O: OutputPin,
 {
     pub const fn new(cols: [O; W], rows: [I; H]) -> Self {
-        Self { cols, rows }
+        Self::with_config(cols, rows, MatrixConfig::new())
+    }
+
+    pub const fn with_config(cols: [O; W], rows: [I; H], config: MatrixConfig) -> Self {
+        Self { cols, rows, config }
     }
 
-    pub const fn debounced(cols: [O; W], rows: [I; H]) -> impl Scan<W, H> {
+    pub const fn debounced(cols: [O; W], rows: [I; H]) -> Debounce<Self, W, H> {
         debounce(Self::new(cols, rows))
     }
+
+    pub const fn debounced_with_config(
+        cols: [O; W],
+        rows: [I; H],
+        config: MatrixConfig,
+    ) -> Debounce<Self, W, H> {
+        debounce(Self::with_config(cols, rows, config))
+    }
+}
+
+impl<I, O, const W: usize, const H: usize> Col2Row<I, O, W, H>
+where
+    I: InputPin + Wait,
+    O: OutputPin,
+{
+    /// Wraps the scanner so the keyboard can wait for a key press instead of scanning while idle.
+    pub const fn idle(self) -> Idle<Self> {
+        Idle::new(self)
+    }
+
+    pub(super) async fn wait_for_any_row(&mut self) -> Result<(), ScanError> {
+        let config = self.config;
+
+        for pin in self.cols.iter_mut() {
+            config.select_line(pin).map_err(ScanError::pin)?;
+        }
+        config.wait_settle().await;
+
+        let (result, _) =
+            select_array(self.rows.each_mut().map(|pin| config.wait_for_active(pin))).await;
+
+        for pin in self.cols.iter_mut() {
+            config.unselect_line(pin).map_err(ScanError::pin)?;
+        }
+        config.wait_unselect().await;
+
+        result.map_err(ScanError::pin)
+    }
 }
 
 impl<I, O, const W: usize, const H: usize> Scan<W, H> for Col2Row<I, O, W, H>
 where
-    I: InputPin<Error = Infallible>,
-    O: OutputPin<Error = Infallible>,
+    I: InputPin,
+    O: OutputPin,
 {
-    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> () {
+    type Error = ScanError;
+
+    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> Result<(), ScanError> {
+        let config = self.config;
+
+        // Lines start out in an unknown state, and may be left selected by a failed scan.
+        for pin in self.cols.iter_mut() {
+            config.unselect_line(pin).map_err(ScanError::pin)?;
+        }
+
         for (x, col_pin) in self.cols.iter_mut().enumerate() {
-            col_pin.set_high().unwrap();
-            Timer::after_micros(30).await;
+            config.select_line(col_pin).map_err(ScanError::pin)?;
+            config.wait_settle().await;
 
             for (y, row_pin) in self.rows.iter_mut().enumerate() {
-                buf[y][x] = row_pin.is_high().unwrap();
+                buf[y][x] = config.is_active(row_pin).map_err(ScanError::pin)?;
             }
 
-            col_pin.set_low().unwrap();
-            Timer::after_micros(30).await;
+            config.unselect_line(col_pin).map_err(ScanError::pin)?;
+            config.wait_unselect().await;
         }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use embassy_futures::block_on;
+    use embassy_time::Duration;
+    use embedded_hal::digital::ErrorKind;
+
+    use crate::scan::mock::{FailingPin, MockInput, MockOutput};
+
+    use super::*;
+
+    #[test]
+    fn scan() {
+        let col = &Cell::new(None);
+        let cols = [0, 1].map(|x| {
+            MockOutput::new(move |high| {
+                if high {
+                    col.set(Some(x))
+                }
+            })
+        });
+        let rows = [MockInput::new(|| col.get() == Some(1))];
+
+        let mut buf = [[false; 2]; 1];
+        block_on(Col2Row::new(cols, rows).scan(&mut buf)).unwrap();
+
+        assert_eq!(buf, [[false, true]]);
+    }
+
+    #[test]
+    fn scan_active_low() {
+        let col = &Cell::new(None);
+        let cols = [0, 1].map(|x| {
+            MockOutput::new(move |high| {
+                if !high {
+                    col.set(Some(x))
+                } else if col.get() == Some(x) {
+                    col.set(None)
+                }
+            })
+        });
+        let rows = [MockInput::new(|| col.get() != Some(0))];
+
+        let config = MatrixConfig::active_low().unselect(Duration::MIN);
+        let mut buf = [[false; 2]; 1];
+        block_on(Col2Row::with_config(cols, rows, config).scan(&mut buf)).unwrap();
+
+        assert_eq!(buf, [[true, false]]);
+        assert_eq!(col.get(), None);
+    }
+
+    #[test]
+    fn propagates_pin_errors() {
+        let cols = [MockOutput::new(|_| {})];
+        let mut buf = [[false; 1]; 1];
+
+        assert_eq!(
+            block_on(Col2Row::new(cols, [FailingPin]).scan(&mut buf)),
+            Err(ScanError::Pin(ErrorKind::Other))
+        );
     }
 }
@@ -0,0 +1,321 @@
+use embassy_futures::{
+    join::join,
+    select::{Either, select},
+};
+
+use super::{Debounce, Scan, ScanError, debounce::debounce};
+
+/// Scanner combining several scanners into one matrix, e.g. a [`Col2Row`](super::Col2Row) for
+/// the main keys and a [`Direct`](super::Direct) for thumb keys.
+///
+/// Each scanner is either placed at an offset, or has each of its keys remapped to a position.
+/// All scanners are scanned concurrently, and positions no scanner maps to read as released. The
+/// errors of the scanners must convert into a [`ScanError`].
+///
+/// While idle, the keyboard waits for a press on any of the scanners, unless one of them is
+/// unable to wait.
+pub struct Composite<const W: usize, const H: usize, P = ()> {
+    parts: P,
+}
+
+impl<const W: usize, const H: usize> Composite<W, H> {
+    pub const fn new() -> Self {
+        Self { parts: () }
+    }
+}
+
+impl<const W: usize, const H: usize> Default for Composite<W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize, P> Composite<W, H, P> {
+    /// Adds a scanner whose top-left key is placed at column `x` and row `y`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix of the scanner does not fit in the composite at that position.
+    pub fn with<S, const SW: usize, const SH: usize>(
+        self,
+        scanner: S,
+        x: usize,
+        y: usize,
+    ) -> Composite<W, H, (P, Part<S, SW, SH>)>
+    where
+        S: Scan<SW, SH>,
+    {
+        assert!(
+            x + SW <= W && y + SH <= H,
+            "scanner at ({}, {}) does not fit",
+            x,
+            y
+        );
+        self.push(Part::new(scanner, Placement::Offset(x, y)))
+    }
+
+    /// Adds a scanner whose keys are each placed at the given `(x, y)` position, or dropped if
+    /// `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a position is outside the composite.
+    pub fn with_remapped<S, const SW: usize, const SH: usize>(
+        self,
+        scanner: S,
+        map: [[Option<(usize, usize)>; SW]; SH],
+    ) -> Composite<W, H, (P, Part<S, SW, SH>)>
+    where
+        S: Scan<SW, SH>,
+    {
+        for (x, y) in map.iter().flatten().flatten() {
+            assert!(*x < W && *y < H, "({}, {}) is outside the matrix", x, y);
+        }
+        self.push(Part::new(scanner, Placement::Remap(map)))
+    }
+
+    pub const fn debounced(self) -> Debounce<Self, W, H>
+    where
+        Self: Scan<W, H, Error = ScanError>,
+    {
+        debounce(self)
+    }
+
+    fn push<T>(self, part: T) -> Composite<W, H, (P, T)> {
+        Composite {
+            parts: (self.parts, part),
+        }
+    }
+}
+
+impl<const W: usize, const H: usize, P> Scan<W, H> for Composite<W, H, P>
+where
+    P: Parts<W, H>,
+{
+    type Error = ScanError;
+
+    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> Result<(), ScanError> {
+        self.parts.scan().await?;
+
+        *buf = [[false; W]; H];
+        self.parts.place(buf);
+        Ok(())
+    }
+
+    async fn wait_for_press(&mut self) -> bool {
+        self.parts.wait_for_press().await
+    }
+
+    fn travel(&self, x: usize, y: usize) -> Option<u8> {
+        self.parts.travel(x, y)
+    }
+}
+
+/// A scanner added to a [`Composite`], together with its own matrix.
+pub struct Part<S, const W: usize, const H: usize> {
+    scanner: S,
+    placement: Placement<W, H>,
+    buf: [[bool; W]; H],
+}
+
+enum Placement<const W: usize, const H: usize> {
+    Offset(usize, usize),
+    Remap([[Option<(usize, usize)>; W]; H]),
+}
+
+impl<S, const W: usize, const H: usize> Part<S, W, H> {
+    const fn new(scanner: S, placement: Placement<W, H>) -> Self {
+        Self {
+            scanner,
+            placement,
+            buf: [[false; W]; H],
+        }
+    }
+
+    /// Position in the matrix of the part of the key placed at `x`, `y`, if any.
+    fn source(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        match &self.placement {
+            Placement::Offset(dx, dy) => {
+                let (x, y) = (x.checked_sub(*dx)?, y.checked_sub(*dy)?);
+                (x < W && y < H).then_some((x, y))
+            }
+            Placement::Remap(map) => map.iter().enumerate().find_map(|(sy, row)| {
+                let sx = row.iter().position(|&position| position == Some((x, y)))?;
+                Some((sx, sy))
+            }),
+        }
+    }
+}
+
+/// The parts of a [`Composite`], nested as `((((), A), B), C)`.
+pub trait Parts<const W: usize, const H: usize> {
+    /// Scans every part into its own matrix.
+    fn scan(&mut self) -> impl Future<Output = Result<(), ScanError>>;
+
+    /// Copies the matrix of every part into `buf`.
+    fn place(&self, buf: &mut [[bool; W]; H]);
+
+    /// Waits until any part might have been pressed, or returns `false` as soon as a part is
+    /// unable to wait.
+    fn wait_for_press(&mut self) -> impl Future<Output = bool>;
+
+    /// Travel of the key at `x`, `y`, read from the last part placing a key there.
+    fn travel(&self, x: usize, y: usize) -> Option<u8>;
+}
+
+impl<const W: usize, const H: usize> Parts<W, H> for () {
+    async fn scan(&mut self) -> Result<(), ScanError> {
+        Ok(())
+    }
+
+    fn place(&self, _buf: &mut [[bool; W]; H]) {}
+
+    async fn wait_for_press(&mut self) -> bool {
+        // Without parts, no key is ever pressed.
+        core::future::pending().await
+    }
+
+    fn travel(&self, _x: usize, _y: usize) -> Option<u8> {
+        None
+    }
+}
+
+impl<P, S, const SW: usize, const SH: usize, const W: usize, const H: usize> Parts<W, H>
+    for (P, Part<S, SW, SH>)
+where
+    P: Parts<W, H>,
+    S: Scan<SW, SH>,
+    S::Error: Into<ScanError>,
+{
+    async fn scan(&mut self) -> Result<(), ScanError> {
+        let (parts, part) = self;
+        let (parts, part) = join(parts.scan(), part.scanner.scan(&mut part.buf)).await;
+        parts.and(part.map_err(Into::into))
+    }
+
+    fn place(&self, buf: &mut [[bool; W]; H]) {
+        let (parts, part) = self;
+        parts.place(buf);
+
+        for (y, row) in part.buf.iter().enumerate() {
+            for (x, &pressed) in row.iter().enumerate() {
+                let (x, y) = match &part.placement {
+                    Placement::Offset(dx, dy) => (x + dx, y + dy),
+                    Placement::Remap(map) => match map[y][x] {
+                        Some(position) => position,
+                        None => continue,
+                    },
+                };
+
+                buf[y][x] = pressed;
+            }
+        }
+    }
+
+    async fn wait_for_press(&mut self) -> bool {
+        let (parts, part) = self;
+        match select(parts.wait_for_press(), part.scanner.wait_for_press()).await {
+            Either::First(woke) | Either::Second(woke) => woke,
+        }
+    }
+
+    fn travel(&self, x: usize, y: usize) -> Option<u8> {
+        let (parts, part) = self;
+        match part.source(x, y) {
+            Some((x, y)) => part.scanner.travel(x, y),
+            None => parts.travel(x, y),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use embassy_futures::{block_on, poll_once};
+
+    use crate::scan::{
+        FULL_TRAVEL,
+        mock::{MockMatrix, scan},
+    };
+
+    use super::*;
+
+    #[test]
+    fn places_at_offsets() {
+        let (square, row) = (
+            Cell::new([[true, false], [false, true]]),
+            Cell::new([[true; 2]]),
+        );
+        let mut scanner = Composite::<3, 3>::new()
+            .with(MockMatrix::new(&square), 0, 0)
+            .with(MockMatrix::new(&row), 1, 2);
+
+        assert_eq!(
+            scan(&mut scanner),
+            [
+                [true, false, false],
+                [false, true, false],
+                [false, true, true]
+            ]
+        );
+    }
+
+    #[test]
+    fn places_remapped() {
+        let keys = Cell::new([[true, false, true]]);
+        let mut scanner = Composite::<2, 2>::new()
+            .with_remapped(MockMatrix::new(&keys), [[Some((1, 1)), Some((0, 0)), None]]);
+
+        assert_eq!(scan(&mut scanner), [[false, false], [false, true]]);
+    }
+
+    #[test]
+    fn reads_travel_of_placed_keys() {
+        let (left, right) = (Cell::new([[true, false]]), Cell::new([[true, false]]));
+        let scanner = Composite::<3, 2>::new()
+            .with(MockMatrix::new(&left).analog(), 0, 0)
+            .with_remapped(MockMatrix::new(&right), [[Some((2, 1)), None]]);
+
+        assert_eq!(scanner.travel(0, 0), Some(FULL_TRAVEL));
+        assert_eq!(scanner.travel(1, 0), Some(0));
+        assert_eq!(scanner.travel(2, 1), None);
+        assert_eq!(scanner.travel(0, 1), None);
+    }
+
+    #[test]
+    fn waits_for_press_on_any_part() {
+        let (left, right) = (Cell::new([[false]]), Cell::new([[false]]));
+        let mut scanner = Composite::<2, 1>::new()
+            .with(MockMatrix::new(&left).waiting(), 0, 0)
+            .with(MockMatrix::new(&right).waiting(), 1, 0);
+
+        assert!(poll_once(scanner.wait_for_press()).is_pending());
+        right.set([[true]]);
+        assert!(block_on(scanner.wait_for_press()));
+    }
+
+    #[test]
+    fn does_not_wait_if_a_part_cannot() {
+        let (left, right) = (Cell::new([[false]]), Cell::new([[false]]));
+        let mut scanner = Composite::<2, 1>::new()
+            .with(MockMatrix::new(&left).waiting(), 0, 0)
+            .with(MockMatrix::new(&right), 1, 0);
+
+        assert!(!block_on(scanner.wait_for_press()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_offset_outside_matrix() {
+        let keys = Cell::new([[false; 2]]);
+        let _ = Composite::<3, 1>::new().with(MockMatrix::new(&keys), 2, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_remap_outside_matrix() {
+        let keys = Cell::new([[false]]);
+        let _ = Composite::<2, 2>::new().with_remapped(MockMatrix::new(&keys), [[Some((0, 2))]]);
+    }
+}
@@ -0,0 +1,126 @@
+use embassy_time::{Duration, Timer};
+use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal_async::digital::Wait;
+
+pub const DEFAULT_SETTLE: Duration = Duration::from_micros(30);
+pub const DEFAULT_UNSELECT: Duration = Duration::from_micros(30);
+
+/// Level of a pin meaning a line is selected or a key is pressed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Polarity {
+    ActiveHigh,
+    ActiveLow,
+}
+
+/// Electrical configuration of a [`Col2Row`](super::Col2Row), [`Row2Col`](super::Row2Col),
+/// [`Hc595Col2Row`](super::Hc595Col2Row) or [`Hc165Col2Row`](super::Hc165Col2Row) matrix.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MatrixConfig {
+    /// Time between selecting a line and reading the inputs. Default: 30 µs.
+    settle: Duration,
+    /// Time after unselecting a line before selecting the next, if any. Default: 30 µs.
+    unselect: Duration,
+    /// Level driving the selected line. Default: [`Polarity::ActiveHigh`].
+    drive: Polarity,
+    /// Level of an input whose key is pressed. Default: [`Polarity::ActiveHigh`], for inputs with
+    /// pull-downs.
+    input: Polarity,
+}
+
+impl MatrixConfig {
+    pub const fn new() -> Self {
+        Self {
+            settle: DEFAULT_SETTLE,
+            unselect: DEFAULT_UNSELECT,
+            drive: Polarity::ActiveHigh,
+            input: Polarity::ActiveHigh,
+        }
+    }
+
+    /// Configuration for lines selected by driving them low and inputs with pull-ups, as on most
+    /// QMK boards.
+    pub const fn active_low() -> Self {
+        Self::new()
+            .drive(Polarity::ActiveLow)
+            .input(Polarity::ActiveLow)
+    }
+
+    pub const fn settle(mut self, settle: Duration) -> Self {
+        self.settle = settle;
+        self
+    }
+
+    /// Sets the delay after unselecting a line, with [`Duration::MIN`] for none.
+    pub const fn unselect(mut self, unselect: Duration) -> Self {
+        self.unselect = unselect;
+        self
+    }
+
+    pub const fn drive(mut self, polarity: Polarity) -> Self {
+        self.drive = polarity;
+        self
+    }
+
+    pub const fn input(mut self, polarity: Polarity) -> Self {
+        self.input = polarity;
+        self
+    }
+
+    pub(crate) fn select_line<O: OutputPin>(&self, pin: &mut O) -> Result<(), O::Error> {
+        match self.drive {
+            Polarity::ActiveHigh => pin.set_high(),
+            Polarity::ActiveLow => pin.set_low(),
+        }
+    }
+
+    pub(crate) fn unselect_line<O: OutputPin>(&self, pin: &mut O) -> Result<(), O::Error> {
+        match self.drive {
+            Polarity::ActiveHigh => pin.set_low(),
+            Polarity::ActiveLow => pin.set_high(),
+        }
+    }
+
+    /// Waits for the inputs to settle after selecting a line.
+    pub(crate) async fn wait_settle(&self) {
+        Timer::after(self.settle).await;
+    }
+
+    /// Waits for a line to be fully unselected, if configured to.
+    pub(crate) async fn wait_unselect(&self) {
+        if self.unselect > Duration::MIN {
+            Timer::after(self.unselect).await;
+        }
+    }
+
+    /// Level of an output bit selecting a line if `selected`, or unselecting it.
+    pub(crate) fn line_level(&self, selected: bool) -> bool {
+        selected == (self.drive == Polarity::ActiveHigh)
+    }
+
+    /// Whether an input bit read as `high` means its key is pressed.
+    pub(crate) fn is_active_level(&self, high: bool) -> bool {
+        high == (self.input == Polarity::ActiveHigh)
+    }
+
+    pub(crate) fn is_active<I: InputPin>(&self, pin: &mut I) -> Result<bool, I::Error> {
+        match self.input {
+            Polarity::ActiveHigh => pin.is_high(),
+            Polarity::ActiveLow => pin.is_low(),
+        }
+    }
+
+    pub(crate) async fn wait_for_active<I: Wait>(self, pin: &mut I) -> Result<(), I::Error> {
+        match self.input {
+            Polarity::ActiveHigh => pin.wait_for_high().await,
+            Polarity::ActiveLow => pin.wait_for_low().await,
+        }
+    }
+}
+
+impl Default for MatrixConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
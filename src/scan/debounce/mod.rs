@@ -1,20 +1,473 @@
-// Two variants of debouncing, both implemented based on this paper: https://my.eng.utah.edu/~cs5780/debouncing.pdf.
+// Deferred and counter debouncing are based on this paper: https://my.eng.utah.edu/~cs5780/debouncing.pdf.
 
-mod counter;
-mod simple;
+use core::cell::Cell;
 
-use embassy_time::Duration;
+use critical_section::Mutex;
+use embassy_time::{Duration, Instant};
 
 use crate::SCAN_INTERVAL;
 
 use super::Scan;
 
-pub use counter::Counter;
-pub use simple::Simple;
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(5);
 
-const DEBOUNCE: Duration = Duration::from_millis(5);
-const DEBOUNCE_COUNT: u64 = DEBOUNCE.as_ticks() / SCAN_INTERVAL.as_ticks();
+/// How raw key states are debounced.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DebounceAlgorithm {
+    /// Reports a change once a key has read the same for the debounce time.
+    Deferred,
+    /// Reports a change once every key in its row has read the same for the debounce time.
+    DeferredPerRow,
+    /// Accumulates the time a key reads differently from its state, and decays it while the key
+    /// reads the same, reporting a change once it reaches the debounce time. Isolated bounces
+    /// only delay a change instead of restarting it. Each scan counts for at most
+    /// [`SCAN_INTERVAL`](crate::SCAN_INTERVAL).
+    Counter,
+    /// Reports presses right away, and releases once a key has read released for the release
+    /// time. The press time is unused.
+    EagerPress,
+    /// Reports changes right away, then ignores the key for the debounce time.
+    Eager,
+    /// Reports changes right away, then ignores every key in its row for the debounce time.
+    EagerPerRow,
+}
+
+/// Configuration of the debouncing applied to a scanner.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DebounceConfig {
+    /// Default: [`DebounceAlgorithm::Deferred`].
+    algorithm: DebounceAlgorithm,
+    /// Debounce time of presses. Default: 5 ms.
+    press: Duration,
+    /// Debounce time of releases. Default: 5 ms.
+    release: Duration,
+}
+
+impl DebounceConfig {
+    pub const fn new() -> Self {
+        Self {
+            algorithm: DebounceAlgorithm::Deferred,
+            press: DEFAULT_DEBOUNCE,
+            release: DEFAULT_DEBOUNCE,
+        }
+    }
+
+    pub const fn algorithm(mut self, algorithm: DebounceAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Sets both the press and release time.
+    pub const fn time(self, time: Duration) -> Self {
+        self.press(time).release(time)
+    }
+
+    pub const fn press(mut self, time: Duration) -> Self {
+        self.press = time;
+        self
+    }
+
+    pub const fn release(mut self, time: Duration) -> Self {
+        self.release = time;
+        self
+    }
+
+    /// Debounce time of a change to `pressed`.
+    fn threshold(&self, pressed: bool) -> Duration {
+        if pressed { self.press } else { self.release }
+    }
+}
+
+impl Default for DebounceConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Debounce configuration shared between the application and a [`Debounce`], so it can be
+/// changed while the keyboard runs, e.g. from a settings key or a host command.
+///
+/// Give it to the scanner with [`Debounce::shared`], which follows it from its next scan.
+#[derive(Debug)]
+pub struct SharedDebounceConfig {
+    config: Mutex<Cell<DebounceConfig>>,
+}
+
+impl SharedDebounceConfig {
+    pub const fn new(config: DebounceConfig) -> Self {
+        Self {
+            config: Mutex::new(Cell::new(config)),
+        }
+    }
+
+    pub fn get(&self) -> DebounceConfig {
+        critical_section::with(|cs| self.config.borrow(cs).get())
+    }
+
+    pub fn set(&self, config: DebounceConfig) {
+        critical_section::with(|cs| self.config.borrow(cs).set(config));
+    }
+}
+
+/// Debounces the keys of a scanner.
+pub struct Debounce<S, const W: usize, const H: usize> {
+    scanner: S,
+    config: DebounceConfig,
+    /// Configuration followed on every scan. Default: none.
+    shared: Option<&'static SharedDebounceConfig>,
+    /// Debounced state of every key.
+    states: [[bool; W]; H],
+    /// State of every key in the previous raw scan.
+    raw: [[bool; W]; H],
+    /// Time every key last changed in a raw scan, or when it stops being ignored for eager
+    /// algorithms.
+    keys: [[Instant; W]; H],
+    /// Same as `keys`, for per-row algorithms.
+    rows: [Instant; H],
+    /// Time every key has read differently from its state, for [`DebounceAlgorithm::Counter`].
+    levels: [[Duration; W]; H],
+    last_scan: Option<Instant>,
+}
+
+impl<S, const W: usize, const H: usize> Debounce<S, W, H> {
+    pub const fn new(scanner: S, config: DebounceConfig) -> Self {
+        Self {
+            scanner,
+            config,
+            shared: None,
+            states: [[false; W]; H],
+            raw: [[false; W]; H],
+            keys: [[Instant::MIN; W]; H],
+            rows: [Instant::MIN; H],
+            levels: [[Duration::MIN; W]; H],
+            last_scan: None,
+        }
+    }
+
+    /// Follows `config` instead of the configuration given to [`new`](Self::new).
+    pub const fn shared(mut self, config: &'static SharedDebounceConfig) -> Self {
+        self.shared = Some(config);
+        self
+    }
+
+    pub fn config(&self) -> DebounceConfig {
+        self.config
+    }
+
+    /// Changes the configuration, keeping the debounced state of every key. Pending changes
+    /// restart their debounce time.
+    pub fn set_config(&mut self, config: DebounceConfig) {
+        let now = self.last_scan.unwrap_or(Instant::MIN);
+        self.config = config;
+        self.keys = [[now; W]; H];
+        self.rows = [now; H];
+        self.levels = [[Duration::MIN; W]; H];
+    }
+
+    /// Replaces the raw scan in `buf` with the debounced state at `now`.
+    fn update(&mut self, buf: &mut [[bool; W]; H], now: Instant) {
+        let elapsed = self
+            .last_scan
+            .map_or(Duration::MIN, |last| (now - last).min(SCAN_INTERVAL));
+        self.last_scan = Some(now);
+
+        for (y, row) in buf.iter().enumerate() {
+            let row_changed = *row != self.raw[y];
+            let row_ready = now >= self.rows[y];
+
+            for (x, &raw) in row.iter().enumerate() {
+                let changed = raw != self.raw[y][x];
+                let differs = raw != self.states[y][x];
+                let threshold = self.config.threshold(raw);
+
+                if changed && self.config.algorithm != DebounceAlgorithm::Eager {
+                    self.keys[y][x] = now;
+                }
+
+                let accept = match self.config.algorithm {
+                    DebounceAlgorithm::Deferred => now - self.keys[y][x] >= threshold,
+                    DebounceAlgorithm::DeferredPerRow => {
+                        !row_changed && now - self.rows[y] >= threshold
+                    }
+                    DebounceAlgorithm::Counter => {
+                        let level = &mut self.levels[y][x];
+                        *level = if differs {
+                            *level + elapsed
+                        } else {
+                            *level - (*level).min(elapsed)
+                        };
+                        *level >= threshold
+                    }
+                    DebounceAlgorithm::EagerPress => raw || now - self.keys[y][x] >= threshold,
+                    DebounceAlgorithm::Eager => now >= self.keys[y][x],
+                    DebounceAlgorithm::EagerPerRow => row_ready,
+                };
+
+                if differs && accept {
+                    self.states[y][x] = raw;
+                    self.levels[y][x] = Duration::MIN;
+
+                    match self.config.algorithm {
+                        DebounceAlgorithm::Eager => self.keys[y][x] = now + threshold,
+                        DebounceAlgorithm::EagerPerRow => self.rows[y] = now + threshold,
+                        _ => {}
+                    }
+                }
+            }
+
+            if row_changed && self.config.algorithm == DebounceAlgorithm::DeferredPerRow {
+                self.rows[y] = now;
+            }
+        }
+
+        self.raw = *buf;
+        *buf = self.states;
+    }
+}
+
+impl<S: Scan<W, H>, const W: usize, const H: usize> Scan<W, H> for Debounce<S, W, H> {
+    type Error = S::Error;
+
+    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> Result<(), S::Error> {
+        self.scanner.scan(buf).await?;
+
+        if let Some(shared) = self.shared {
+            let config = shared.get();
+            if config != self.config {
+                self.set_config(config);
+            }
+        }
+        self.update(buf, Instant::now());
+        Ok(())
+    }
+
+    async fn wait_for_press(&mut self) -> bool {
+        self.scanner.wait_for_press().await
+    }
+
+    fn travel(&self, x: usize, y: usize) -> Option<u8> {
+        self.scanner.travel(x, y)
+    }
+}
+
+pub const fn debounce<S: Scan<W, H>, const W: usize, const H: usize>(
+    scanner: S,
+) -> Debounce<S, W, H> {
+    Debounce::new(scanner, DebounceConfig::new())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use core::cell::Cell;
+
+    use proptest::prelude::*;
+
+    use crate::scan::{
+        Composite,
+        mock::{MockMatrix, scan},
+    };
+
+    use super::*;
+
+    /// Feeds one raw sample per millisecond for a single row, returning the debounced samples.
+    fn run<const W: usize>(config: DebounceConfig, samples: &[[bool; W]]) -> [[bool; W]; 32] {
+        let mut debounce = Debounce::<(), W, 1>::new((), config);
+        let mut out = [[false; W]; 32];
+
+        for (ms, sample) in samples.iter().enumerate() {
+            let mut buf = [*sample];
+            debounce.update(&mut buf, Instant::from_millis(ms as u64));
+            out[ms] = buf[0];
+        }
+        out
+    }
+
+    fn debounced(config: DebounceConfig, samples: &[u8]) -> [u8; 32] {
+        let mut buf = [[false; 1]; 32];
+        for (dst, &sample) in buf.iter_mut().zip(samples) {
+            *dst = [sample != 0];
+        }
+
+        run(config, &buf[..samples.len()]).map(|[pressed]| pressed as u8)
+    }
+
+    const CONFIG: DebounceConfig = DebounceConfig::new().time(Duration::from_millis(3));
+
+    #[test]
+    fn deferred_waits_for_stable_key() {
+        let out = debounced(CONFIG, &[1, 0, 1, 1, 1, 1, 1, 0, 1, 0, 0, 0, 0]);
+        assert_eq!(out[..13], [0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn deferred_uses_press_and_release_times() {
+        let config = CONFIG.press(Duration::from_millis(1));
+        let out = debounced(config, &[1, 1, 1, 0, 0, 0, 0]);
+        assert_eq!(out[..7], [0, 1, 1, 1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn deferred_per_row_waits_for_whole_row() {
+        let config = CONFIG.algorithm(DebounceAlgorithm::DeferredPerRow);
+        let samples = [
+            [true, false],
+            [true, false],
+            [true, true],
+            [true, true],
+            [true, true],
+            [true, true],
+        ];
+        let out = run(config, &samples);
+        assert_eq!(
+            out[..6],
+            [
+                [false; 2], [false; 2], [false; 2], [false; 2], [false; 2], [true; 2]
+            ]
+        );
+    }
+
+    #[test]
+    fn counter_tolerates_isolated_bounces() {
+        let config = CONFIG.algorithm(DebounceAlgorithm::Counter);
+        let out = debounced(config, &[1, 1, 0, 1, 1, 1]);
+        assert_eq!(out[..6], [0, 0, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn eager_press_reports_press_right_away() {
+        let config = CONFIG.algorithm(DebounceAlgorithm::EagerPress);
+        let out = debounced(config, &[1, 0, 1, 0, 0, 0, 0]);
+        assert_eq!(out[..7], [1, 1, 1, 1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn eager_ignores_bounces() {
+        let config = CONFIG.algorithm(DebounceAlgorithm::Eager);
+        let out = debounced(config, &[1, 0, 1, 0, 0, 0, 1]);
+        assert_eq!(out[..7], [1, 1, 1, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn eager_per_row_ignores_whole_row() {
+        let config = CONFIG.algorithm(DebounceAlgorithm::EagerPerRow);
+        let samples = [[true, false], [true, true], [true, true], [true, true]];
+        let out = run(config, &samples);
+        assert_eq!(
+            out[..4],
+            [[true, false], [true, false], [true, false], [true, true]]
+        );
+    }
+
+    #[test]
+    fn set_config_restarts_pending_changes() {
+        let mut debounce = Debounce::<(), 1, 1>::new((), CONFIG);
+        debounce.update(&mut [[false]], Instant::from_millis(0));
+        debounce.update(&mut [[true]], Instant::from_millis(10));
+        debounce.set_config(CONFIG.time(Duration::from_millis(1)));
+
+        let mut buf = [[true]];
+        debounce.update(&mut buf, Instant::from_millis(10));
+        assert_eq!(buf, [[false]]);
+
+        buf = [[true]];
+        debounce.update(&mut buf, Instant::from_millis(11));
+        assert_eq!(buf, [[true]]);
+    }
+
+    #[test]
+    fn follows_shared_config_while_scanning() {
+        static SHARED: SharedDebounceConfig =
+            SharedDebounceConfig::new(DebounceConfig::new().time(Duration::from_secs(60)));
+
+        let keys = Cell::new([[false]]);
+        let mut scanner = Composite::<1, 1>::new()
+            .with(MockMatrix::new(&keys), 0, 0)
+            .debounced()
+            .shared(&SHARED);
+
+        keys.set([[true]]);
+        assert_eq!(scan(&mut scanner), [[false]]);
+        assert_eq!(scanner.config(), SHARED.get());
+
+        SHARED.set(DebounceConfig::new().time(Duration::MIN));
+        assert_eq!(scan(&mut scanner), [[true]]);
+        assert_eq!(scanner.config(), SHARED.get());
+    }
+
+    /// Raw samples with bounces, followed by a stable tail of random length.
+    fn bouncing() -> impl Strategy<Value = (Vec<bool>, bool, usize)> {
+        (
+            proptest::collection::vec(any::<bool>(), 0..256),
+            any::<bool>(),
+            0..256usize,
+        )
+    }
+
+    proptest! {
+        /// A key changes exactly when it has read the same for its whole debounce time, which
+        /// may span any number of scans.
+        #[test]
+        fn deferred_matches_model(
+            (bounces, last, tail) in bouncing(),
+            press in 0..200u64,
+            release in 0..200u64,
+        ) {
+            let config = DebounceConfig::new()
+                .press(Duration::from_millis(press))
+                .release(Duration::from_millis(release));
+            let mut debounce = Debounce::<(), 1, 1>::new((), config);
+            let samples: Vec<bool> = bounces
+                .into_iter()
+                .chain(core::iter::repeat_n(last, tail))
+                .collect();
+
+            let mut state = false;
+            for (ms, &raw) in samples.iter().enumerate() {
+                let time = if raw { press } else { release } as usize;
+                let stable = samples[ms.saturating_sub(time)..=ms].iter().all(|&s| s == raw);
+                // Keys read released before the first scan.
+                if stable && (!raw || ms >= time) {
+                    state = raw;
+                }
+
+                let mut buf = [[raw]];
+                debounce.update(&mut buf, Instant::from_millis(ms as u64));
+                prop_assert_eq!(buf, [[state]], "at {} ms", ms);
+            }
+        }
+
+        /// Every algorithm follows a key once it has been stable for long enough.
+        #[test]
+        fn every_algorithm_settles(
+            (bounces, last, _) in bouncing(),
+            algorithm in prop_oneof![
+                Just(DebounceAlgorithm::Deferred),
+                Just(DebounceAlgorithm::DeferredPerRow),
+                Just(DebounceAlgorithm::Counter),
+                Just(DebounceAlgorithm::EagerPress),
+                Just(DebounceAlgorithm::Eager),
+                Just(DebounceAlgorithm::EagerPerRow),
+            ],
+            time in 0..100u64,
+        ) {
+            let config = DebounceConfig::new()
+                .algorithm(algorithm)
+                .time(Duration::from_millis(time));
+            let mut debounce = Debounce::<(), 1, 1>::new((), config);
 
-pub const fn debounce<const W: usize, const H: usize>(scanner: impl Scan<W, H>) -> impl Scan<W, H> {
-    Simple::new(scanner)
+            let mut buf = [[false]];
+            let settled = bounces.len() + time as usize + 1;
+            for ms in 0..=settled {
+                buf = [[bounces.get(ms).copied().unwrap_or(last)]];
+                debounce.update(&mut buf, Instant::from_millis(ms as u64));
+            }
+            prop_assert_eq!(buf, [[last]]);
+        }
+    }
 }
@@ -1,20 +1,52 @@
-use core::convert::Infallible;
-
+use embassy_futures::select::select_array;
 use embedded_hal::digital::InputPin;
+use embedded_hal_async::digital::Wait;
 
-use super::Scan;
+use super::{Debounce, Idle, Scan, ScanError, debounce::debounce};
 
 pub struct Direct<P, const N: usize> {
     pins: [P; N],
 }
 
+impl<P, const N: usize> Direct<P, N>
+where
+    P: InputPin,
+{
+    pub const fn new(pins: [P; N]) -> Self {
+        Self { pins }
+    }
+
+    pub const fn debounced(pins: [P; N]) -> Debounce<Self, N, 1> {
+        debounce(Self::new(pins))
+    }
+}
+
+impl<P, const N: usize> Direct<P, N>
+where
+    P: InputPin + Wait,
+{
+    /// Wraps the scanner so the keyboard can wait for a key press instead of scanning while idle.
+    pub const fn idle(self) -> Idle<Self> {
+        Idle::new(self)
+    }
+
+    pub(super) async fn wait_for_any_pin(&mut self) -> Result<(), ScanError> {
+        let (result, _) = select_array(self.pins.each_mut().map(Wait::wait_for_high)).await;
+        result.map_err(ScanError::pin)
+    }
+}
+
 impl<P, const N: usize> Scan<N, 1> for Direct<P, N>
 where
-    P: InputPin<Error = Infallible>,
+    P: InputPin,
 {
-    async fn scan(&mut self, buf: &mut [[bool; N]; 1]) {
+    type Error = ScanError;
+
+    async fn scan(&mut self, buf: &mut [[bool; N]; 1]) -> Result<(), ScanError> {
         for (pin, dst) in self.pins.iter_mut().zip(buf[0].iter_mut()) {
-            *dst = pin.is_high().unwrap();
+            *dst = pin.is_high().map_err(ScanError::pin)?;
         }
+
+        Ok(())
     }
 }
@@ -0,0 +1,212 @@
+use embassy_time::{Duration, Timer};
+use embedded_hal::digital::{InputPin, OutputPin};
+
+use super::{Debounce, Scan, ScanError, debounce::debounce};
+
+pub const DEFAULT_DUPLEX_SETTLE: Duration = Duration::from_micros(30);
+
+/// Scanner for duplex matrices, where every crossing of a row and a column holds two keys with
+/// opposite diodes.
+///
+/// Pins must be bidirectional, like open-drain outputs with pull-ups, so that setting them high
+/// releases the line and lets it be read. Columns are driven low one at a time to read keys with
+/// diodes from rows to columns, then rows are driven low to read keys with diodes from columns to
+/// rows.
+///
+/// The two keys at column `x` are placed side by side in a matrix twice as wide, at `2 * x` for
+/// the column to row key and `2 * x + 1` for the row to column key.
+pub struct Duplex<P, const C: usize, const W: usize, const H: usize> {
+    cols: [P; C],
+    rows: [P; H],
+    /// Time between driving or releasing a line and reading the others. Default: 30 µs.
+    settle: Duration,
+}
+
+impl<P, const C: usize, const W: usize, const H: usize> Duplex<P, C, W, H>
+where
+    P: InputPin + OutputPin,
+{
+    const _ASSERT: () = assert!(W == 2 * C);
+
+    pub const fn new(cols: [P; C], rows: [P; H]) -> Self {
+        let () = Self::_ASSERT;
+        Self {
+            cols,
+            rows,
+            settle: DEFAULT_DUPLEX_SETTLE,
+        }
+    }
+
+    pub const fn settle(mut self, settle: Duration) -> Self {
+        self.settle = settle;
+        self
+    }
+
+    pub const fn debounced(cols: [P; C], rows: [P; H]) -> Debounce<Self, W, H> {
+        debounce(Self::new(cols, rows))
+    }
+}
+
+impl<P, const C: usize, const W: usize, const H: usize> Scan<W, H> for Duplex<P, C, W, H>
+where
+    P: InputPin + OutputPin,
+{
+    type Error = ScanError;
+
+    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> Result<(), ScanError> {
+        scan_duplex(
+            &mut self.cols,
+            &mut self.rows,
+            self.settle,
+            |x, y, col_to_row, pressed| {
+                buf[y][2 * x + usize::from(!col_to_row)] = pressed;
+            },
+        )
+        .await
+    }
+}
+
+/// Scanner for Japanese duplex matrices, where every crossing of a row and a column holds two
+/// keys with opposite diodes, one in each of two physical rows sharing the row pin.
+///
+/// Pins are driven and read as for a [`Duplex`], but the two keys at row `y` are placed one above
+/// the other in a matrix twice as tall, at `2 * y` for the column to row key and `2 * y + 1` for
+/// the row to column key.
+pub struct JapaneseDuplex<P, const R: usize, const W: usize, const H: usize> {
+    cols: [P; W],
+    rows: [P; R],
+    /// Time between driving or releasing a line and reading the others. Default: 30 µs.
+    settle: Duration,
+}
+
+impl<P, const R: usize, const W: usize, const H: usize> JapaneseDuplex<P, R, W, H>
+where
+    P: InputPin + OutputPin,
+{
+    const _ASSERT: () = assert!(H == 2 * R);
+
+    pub const fn new(cols: [P; W], rows: [P; R]) -> Self {
+        let () = Self::_ASSERT;
+        Self {
+            cols,
+            rows,
+            settle: DEFAULT_DUPLEX_SETTLE,
+        }
+    }
+
+    pub const fn settle(mut self, settle: Duration) -> Self {
+        self.settle = settle;
+        self
+    }
+
+    pub const fn debounced(cols: [P; W], rows: [P; R]) -> Debounce<Self, W, H> {
+        debounce(Self::new(cols, rows))
+    }
+}
+
+impl<P, const R: usize, const W: usize, const H: usize> Scan<W, H> for JapaneseDuplex<P, R, W, H>
+where
+    P: InputPin + OutputPin,
+{
+    type Error = ScanError;
+
+    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> Result<(), ScanError> {
+        scan_duplex(
+            &mut self.cols,
+            &mut self.rows,
+            self.settle,
+            |x, y, col_to_row, pressed| {
+                buf[2 * y + usize::from(!col_to_row)][x] = pressed;
+            },
+        )
+        .await
+    }
+}
+
+/// Reads every key of a duplex matrix, passing `place` its column and row, whether its diode goes
+/// from the column to the row, and whether it is pressed.
+async fn scan_duplex<P, const C: usize, const R: usize>(
+    cols: &mut [P; C],
+    rows: &mut [P; R],
+    settle: Duration,
+    mut place: impl FnMut(usize, usize, bool, bool),
+) -> Result<(), ScanError>
+where
+    P: InputPin + OutputPin,
+{
+    // Pins may start out driven, or be left driven by a failed scan.
+    for pin in cols.iter_mut().chain(rows.iter_mut()) {
+        pin.set_high().map_err(ScanError::pin)?;
+    }
+
+    for (x, col_pin) in cols.iter_mut().enumerate() {
+        col_pin.set_low().map_err(ScanError::pin)?;
+        Timer::after(settle).await;
+
+        for (y, row_pin) in rows.iter_mut().enumerate() {
+            place(x, y, false, row_pin.is_low().map_err(ScanError::pin)?);
+        }
+
+        col_pin.set_high().map_err(ScanError::pin)?;
+        Timer::after(settle).await;
+    }
+
+    for (y, row_pin) in rows.iter_mut().enumerate() {
+        row_pin.set_low().map_err(ScanError::pin)?;
+        Timer::after(settle).await;
+
+        for (x, col_pin) in cols.iter_mut().enumerate() {
+            place(x, y, true, col_pin.is_low().map_err(ScanError::pin)?);
+        }
+
+        row_pin.set_high().map_err(ScanError::pin)?;
+        Timer::after(settle).await;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use embassy_futures::block_on;
+
+    use crate::scan::mock::MockLines;
+
+    use super::*;
+
+    #[test]
+    fn scan_both_directions() {
+        // Lines 0 and 1 are columns, 2 and 3 are rows.
+        let lines = MockLines::<4>::new();
+        lines.press(0, 2); // Column 0 to row 0.
+        lines.press(3, 0); // Row 1 to column 0.
+        lines.press(2, 1); // Row 0 to column 1.
+
+        let [c0, c1, r0, r1] = lines.pins();
+        let mut scanner = Duplex::new([c0, c1], [r0, r1]);
+        let mut buf = [[false; 4]; 2];
+        block_on(scanner.scan(&mut buf)).unwrap();
+
+        assert_eq!(
+            buf,
+            [[true, false, false, true], [false, true, false, false]]
+        );
+        assert!(lines.all_released());
+    }
+
+    #[test]
+    fn scan_japanese_both_directions() {
+        // Lines 0 and 1 are columns, 2 is the row.
+        let lines = MockLines::<3>::new();
+        lines.press(0, 2); // Column 0 to row 0.
+        lines.press(2, 1); // Row 0 to column 1.
+
+        let [c0, c1, r0] = lines.pins();
+        let mut scanner = JapaneseDuplex::new([c0, c1], [r0]);
+        let mut buf = [[false; 2]; 2];
+        block_on(scanner.scan(&mut buf)).unwrap();
+
+        assert_eq!(buf, [[true, false], [false, true]]);
+        assert!(lines.all_released());
+    }
+}
@@ -0,0 +1,175 @@
+use embedded_hal_async::i2c::I2c;
+
+use super::{Debounce, Scan, ScanError, debounce::debounce};
+
+/// Supported 16-bit I2C GPIO expanders. All of them use address `0x20` with their address pins
+/// tied low.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ExpanderChip {
+    Mcp23017,
+    Pca9555,
+    /// Register compatible with the PCA9555.
+    Tca9555,
+}
+
+impl ExpanderChip {
+    /// Register writes configuring port A as outputs, all high, and port B as inputs with
+    /// pull-ups.
+    const fn init(self) -> &'static [[u8; 2]] {
+        match self {
+            // OLATA, IODIRA, IODIRB, GPPUB.
+            Self::Mcp23017 => &[[0x14, 0xFF], [0x00, 0x00], [0x01, 0xFF], [0x0D, 0xFF]],
+            // Output 0, configuration 0, configuration 1. Inputs have fixed pull-ups.
+            Self::Pca9555 | Self::Tca9555 => &[[0x02, 0xFF], [0x06, 0x00], [0x07, 0xFF]],
+        }
+    }
+
+    /// Register driving port A.
+    const fn output(self) -> u8 {
+        match self {
+            Self::Mcp23017 => 0x14,
+            Self::Pca9555 | Self::Tca9555 => 0x02,
+        }
+    }
+
+    /// Register reading port B.
+    const fn input(self) -> u8 {
+        match self {
+            Self::Mcp23017 => 0x13,
+            Self::Pca9555 | Self::Tca9555 => 0x01,
+        }
+    }
+}
+
+/// Scanner for a matrix wired to an I2C GPIO expander.
+///
+/// Columns are connected to port A (port 0 on the PCA9555) and driven low one at a time, while
+/// rows are connected to port B (port 1) and read through pull-ups, so diodes point from rows to
+/// columns. Each column costs one register write and a single read of all rows.
+///
+/// The expander is configured on the first scan, and again after any bus error.
+///
+/// Use a [`Composite`](super::Composite) to span a matrix across MCU pins and expander pins.
+pub struct Expander<I, const W: usize, const H: usize> {
+    i2c: I,
+    chip: ExpanderChip,
+    address: u8,
+    is_initialized: bool,
+}
+
+impl<I, const W: usize, const H: usize> Expander<I, W, H>
+where
+    I: I2c,
+{
+    const _ASSERT: () = assert!(W <= 8 && H <= 8);
+
+    pub const fn new(i2c: I, chip: ExpanderChip, address: u8) -> Self {
+        let () = Self::_ASSERT;
+        Self {
+            i2c,
+            chip,
+            address,
+            is_initialized: false,
+        }
+    }
+
+    pub const fn debounced(i2c: I, chip: ExpanderChip, address: u8) -> Debounce<Self, W, H> {
+        debounce(Self::new(i2c, chip, address))
+    }
+
+    async fn init(&mut self) -> Result<(), I::Error> {
+        for write in self.chip.init() {
+            self.i2c.write(self.address, write).await?;
+        }
+
+        self.is_initialized = true;
+        Ok(())
+    }
+
+    async fn try_scan(&mut self, buf: &mut [[bool; W]; H]) -> Result<(), I::Error> {
+        if !self.is_initialized {
+            self.init().await?;
+        }
+
+        let mut rows = [0];
+        for x in 0..W {
+            self.i2c
+                .write(self.address, &[self.chip.output(), !(1 << x)])
+                .await?;
+            self.i2c
+                .write_read(self.address, &[self.chip.input()], &mut rows)
+                .await?;
+
+            for (y, row) in buf.iter_mut().enumerate() {
+                row[x] = rows[0] & (1 << y) == 0;
+            }
+        }
+
+        self.i2c
+            .write(self.address, &[self.chip.output(), 0xFF])
+            .await
+    }
+}
+
+impl<I, const W: usize, const H: usize> Scan<W, H> for Expander<I, W, H>
+where
+    I: I2c,
+{
+    type Error = ScanError;
+
+    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> Result<(), ScanError> {
+        let result = self.try_scan(buf).await;
+        if result.is_err() {
+            self.is_initialized = false;
+        }
+
+        result.map_err(ScanError::i2c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embassy_futures::block_on;
+
+    use crate::scan::mock::MockExpander;
+
+    use super::*;
+
+    const PRESSED: [[bool; 3]; 2] = [[true, false, false], [false, true, true]];
+
+    #[test]
+    fn scan_mcp23017() {
+        let mut expander = MockExpander::new(ExpanderChip::Mcp23017, PRESSED);
+        let mut buf = [[false; 3]; 2];
+        block_on(Expander::new(&mut expander, ExpanderChip::Mcp23017, 0x20).scan(&mut buf))
+            .unwrap();
+
+        assert_eq!(buf, PRESSED);
+        assert_eq!(expander.output(), 0xFF);
+    }
+
+    #[test]
+    fn scan_pca9555() {
+        let mut expander = MockExpander::new(ExpanderChip::Pca9555, PRESSED);
+        let mut buf = [[false; 3]; 2];
+        block_on(Expander::new(&mut expander, ExpanderChip::Pca9555, 0x20).scan(&mut buf)).unwrap();
+
+        assert_eq!(buf, PRESSED);
+    }
+
+    #[test]
+    fn reinitializes_after_error() {
+        let mut expander = MockExpander::new(ExpanderChip::Mcp23017, PRESSED);
+        let mut scanner = Expander::new(&mut expander, ExpanderChip::Mcp23017, 0x20);
+        let mut buf = [[false; 3]; 2];
+        block_on(scanner.scan(&mut buf)).unwrap();
+
+        scanner.i2c.reset();
+        scanner.i2c.fail_next();
+        assert!(block_on(scanner.scan(&mut buf)).is_err());
+
+        block_on(scanner.scan(&mut buf)).unwrap();
+        assert_eq!(buf, PRESSED);
+    }
+}
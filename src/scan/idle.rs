@@ -0,0 +1,72 @@
+use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal_async::digital::Wait;
+
+use super::{Col2Row, Debounce, Direct, Row2Col, Scan, ScanError, debounce::debounce};
+
+/// Lets the keyboard stop scanning while no keys are pressed, by driving all outputs at once and
+/// waiting for any input to go high.
+///
+/// The input pins of the wrapped scanner must implement [`Wait`].
+pub struct Idle<S> {
+    scanner: S,
+}
+
+impl<S> Idle<S> {
+    pub const fn new(scanner: S) -> Self {
+        Self { scanner }
+    }
+
+    pub const fn debounced<const W: usize, const H: usize>(self) -> Debounce<Self, W, H>
+    where
+        Self: Scan<W, H>,
+    {
+        debounce(self)
+    }
+}
+
+impl<I, O, const W: usize, const H: usize> Scan<W, H> for Idle<Col2Row<I, O, W, H>>
+where
+    I: InputPin + Wait,
+    O: OutputPin,
+{
+    type Error = ScanError;
+
+    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> Result<(), ScanError> {
+        self.scanner.scan(buf).await
+    }
+
+    async fn wait_for_press(&mut self) -> bool {
+        self.scanner.wait_for_any_row().await.is_ok()
+    }
+}
+
+impl<I, O, const W: usize, const H: usize> Scan<W, H> for Idle<Row2Col<I, O, W, H>>
+where
+    I: InputPin + Wait,
+    O: OutputPin,
+{
+    type Error = ScanError;
+
+    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> Result<(), ScanError> {
+        self.scanner.scan(buf).await
+    }
+
+    async fn wait_for_press(&mut self) -> bool {
+        self.scanner.wait_for_any_col().await.is_ok()
+    }
+}
+
+impl<P, const N: usize> Scan<N, 1> for Idle<Direct<P, N>>
+where
+    P: InputPin + Wait,
+{
+    type Error = ScanError;
+
+    async fn scan(&mut self, buf: &mut [[bool; N]; 1]) -> Result<(), ScanError> {
+        self.scanner.scan(buf).await
+    }
+
+    async fn wait_for_press(&mut self) -> bool {
+        self.scanner.wait_for_any_pin().await.is_ok()
+    }
+}
@@ -0,0 +1,382 @@
+//! Pin and bus mocks for testing scanners on the host.
+
+use core::{cell::Cell, convert::Infallible, task::Poll};
+
+use embedded_hal::{
+    digital::{self, InputPin, OutputPin},
+    spi::{self, SpiBus},
+};
+use embedded_hal_async::i2c::{self, I2c, Operation};
+
+use embassy_futures::block_on;
+
+use super::{ExpanderChip, FULL_TRAVEL, Scan};
+
+/// Scanner reading its keys from a cell set by the test.
+pub struct MockMatrix<'a, const W: usize, const H: usize> {
+    keys: &'a Cell<[[bool; W]; H]>,
+    is_analog: bool,
+    can_wait: bool,
+}
+
+impl<'a, const W: usize, const H: usize> MockMatrix<'a, W, H> {
+    pub fn new(keys: &'a Cell<[[bool; W]; H]>) -> Self {
+        Self {
+            keys,
+            is_analog: false,
+            can_wait: false,
+        }
+    }
+
+    /// Reads the travel of pressed keys as [`FULL_TRAVEL`] and of released ones as 0.
+    pub fn analog(mut self) -> Self {
+        self.is_analog = true;
+        self
+    }
+
+    /// Waits for a press until any key is pressed, instead of being unable to wait.
+    pub fn waiting(mut self) -> Self {
+        self.can_wait = true;
+        self
+    }
+}
+
+impl<const W: usize, const H: usize> Scan<W, H> for MockMatrix<'_, W, H> {
+    type Error = Infallible;
+
+    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> Result<(), Infallible> {
+        *buf = self.keys.get();
+        Ok(())
+    }
+
+    async fn wait_for_press(&mut self) -> bool {
+        let keys = self.keys;
+        let can_wait = self.can_wait;
+        core::future::poll_fn(|_| {
+            if !can_wait || keys.get().iter().flatten().any(|&pressed| pressed) {
+                Poll::Ready(can_wait)
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    fn travel(&self, x: usize, y: usize) -> Option<u8> {
+        let pressed = self.keys.get()[y][x];
+        self.is_analog
+            .then_some(if pressed { FULL_TRAVEL } else { 0 })
+    }
+}
+
+/// Scans `scanner` once, panicking on failure.
+pub fn scan<const W: usize, const H: usize>(scanner: &mut impl Scan<W, H>) -> [[bool; W]; H] {
+    let mut buf = [[false; W]; H];
+    block_on(scanner.scan(&mut buf)).unwrap();
+    buf
+}
+
+/// Output pin calling a closure with its new state whenever it is set.
+pub struct MockOutput<F> {
+    on_set: F,
+}
+
+impl<F: FnMut(bool)> MockOutput<F> {
+    pub fn new(on_set: F) -> Self {
+        Self { on_set }
+    }
+}
+
+impl<F> digital::ErrorType for MockOutput<F> {
+    type Error = Infallible;
+}
+
+impl<F: FnMut(bool)> OutputPin for MockOutput<F> {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        (self.on_set)(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        (self.on_set)(true);
+        Ok(())
+    }
+}
+
+/// Input pin reading its state from a closure.
+pub struct MockInput<F> {
+    read: F,
+}
+
+impl<F: FnMut() -> bool> MockInput<F> {
+    pub fn new(read: F) -> Self {
+        Self { read }
+    }
+}
+
+impl<F> digital::ErrorType for MockInput<F> {
+    type Error = Infallible;
+}
+
+impl<F: FnMut() -> bool> InputPin for MockInput<F> {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok((self.read)())
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(!(self.read)())
+    }
+}
+
+/// SPI bus calling `write` for every byte written and `read` for every byte read.
+pub struct MockSpi<W, R> {
+    write: W,
+    read: R,
+}
+
+impl<W: FnMut(u8), R: FnMut() -> u8> MockSpi<W, R> {
+    pub fn new(write: W, read: R) -> Self {
+        Self { write, read }
+    }
+}
+
+impl<W, R> spi::ErrorType for MockSpi<W, R> {
+    type Error = Infallible;
+}
+
+impl<W: FnMut(u8), R: FnMut() -> u8> SpiBus for MockSpi<W, R> {
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        words.iter_mut().for_each(|word| *word = (self.read)());
+        Ok(())
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        words.iter().for_each(|&word| (self.write)(word));
+        Ok(())
+    }
+
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        for i in 0..read.len().max(write.len()) {
+            (self.write)(write.get(i).copied().unwrap_or(0));
+            let word = (self.read)();
+            if let Some(slot) = read.get_mut(i) {
+                *slot = word;
+            }
+        }
+        Ok(())
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        for word in words.iter_mut() {
+            (self.write)(*word);
+            *word = (self.read)();
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// MCP23017 or PCA9555 with columns of `pressed` on port A and rows on port B.
+pub struct MockExpander<const W: usize, const H: usize> {
+    chip: ExpanderChip,
+    pressed: [[bool; W]; H],
+    registers: [u8; 0x16],
+    pointer: usize,
+    fail_next: bool,
+}
+
+impl<const W: usize, const H: usize> MockExpander<W, H> {
+    pub fn new(chip: ExpanderChip, pressed: [[bool; W]; H]) -> Self {
+        let mut expander = Self {
+            chip,
+            pressed,
+            registers: [0; 0x16],
+            pointer: 0,
+            fail_next: false,
+        };
+        expander.reset();
+        expander
+    }
+
+    /// Restores the power-on state, with all pins as inputs.
+    pub fn reset(&mut self) {
+        self.registers = [0; 0x16];
+        match self.chip {
+            ExpanderChip::Mcp23017 => self.registers[..2].fill(0xFF),
+            ExpanderChip::Pca9555 | ExpanderChip::Tca9555 => self.registers[0x06..0x08].fill(0xFF),
+        }
+    }
+
+    /// Fails the next transaction.
+    pub fn fail_next(&mut self) {
+        self.fail_next = true;
+    }
+
+    /// Value of the port A output register.
+    pub fn output(&self) -> u8 {
+        match self.chip {
+            ExpanderChip::Mcp23017 => self.registers[0x14],
+            ExpanderChip::Pca9555 | ExpanderChip::Tca9555 => self.registers[0x02],
+        }
+    }
+
+    fn rows(&self) -> u8 {
+        let driven = match self.chip {
+            ExpanderChip::Mcp23017 => !self.registers[0x00],
+            ExpanderChip::Pca9555 | ExpanderChip::Tca9555 => !self.registers[0x06],
+        };
+        let low = driven & !self.output();
+
+        let mut rows = 0xFF;
+        for (y, row) in self.pressed.iter().enumerate() {
+            if (0..W).any(|x| row[x] && low & (1 << x) != 0) {
+                rows &= !(1 << y);
+            }
+        }
+        rows
+    }
+
+    fn register(&self, register: usize) -> u8 {
+        match (self.chip, register) {
+            (ExpanderChip::Mcp23017, 0x13)
+            | (ExpanderChip::Pca9555 | ExpanderChip::Tca9555, 0x01) => self.rows(),
+            _ => self.registers[register],
+        }
+    }
+}
+
+impl<const W: usize, const H: usize> i2c::ErrorType for MockExpander<W, H> {
+    type Error = i2c::ErrorKind;
+}
+
+impl<const W: usize, const H: usize> I2c for MockExpander<W, H> {
+    async fn transaction(
+        &mut self,
+        _address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if core::mem::take(&mut self.fail_next) {
+            return Err(i2c::ErrorKind::NoAcknowledge(
+                i2c::NoAcknowledgeSource::Address,
+            ));
+        }
+
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => {
+                    if let Some((&pointer, data)) = bytes.split_first() {
+                        self.pointer = pointer as usize;
+                        for &byte in data {
+                            self.registers[self.pointer] = byte;
+                            self.pointer += 1;
+                        }
+                    }
+                }
+                Operation::Read(buf) => {
+                    for byte in buf.iter_mut() {
+                        *byte = self.register(self.pointer);
+                        self.pointer += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Open-drain lines with pull-ups, connected by pressed keys in series with diodes.
+pub struct MockLines<const N: usize> {
+    driven_low: [Cell<bool>; N],
+    diodes: [[Cell<bool>; N]; N],
+}
+
+impl<const N: usize> MockLines<N> {
+    pub fn new() -> Self {
+        Self {
+            driven_low: core::array::from_fn(|_| Cell::new(false)),
+            diodes: core::array::from_fn(|_| core::array::from_fn(|_| Cell::new(false))),
+        }
+    }
+
+    /// Presses the key with a diode from `anode` to `cathode`.
+    pub fn press(&self, anode: usize, cathode: usize) {
+        self.diodes[anode][cathode].set(true);
+    }
+
+    pub fn pins(&self) -> [MockLine<'_, N>; N] {
+        core::array::from_fn(|index| MockLine { lines: self, index })
+    }
+
+    pub fn all_released(&self) -> bool {
+        self.driven_low.iter().all(|low| !low.get())
+    }
+
+    fn is_low(&self, index: usize) -> bool {
+        self.driven_low[index].get()
+            || (0..N)
+                .any(|cathode| self.diodes[index][cathode].get() && self.driven_low[cathode].get())
+    }
+}
+
+/// Open-drain pin of a [`MockLines`].
+pub struct MockLine<'a, const N: usize> {
+    lines: &'a MockLines<N>,
+    index: usize,
+}
+
+impl<const N: usize> digital::ErrorType for MockLine<'_, N> {
+    type Error = Infallible;
+}
+
+impl<const N: usize> OutputPin for MockLine<'_, N> {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.lines.driven_low[self.index].set(true);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.lines.driven_low[self.index].set(false);
+        Ok(())
+    }
+}
+
+impl<const N: usize> InputPin for MockLine<'_, N> {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.lines.is_low(self.index))
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.lines.is_low(self.index))
+    }
+}
+
+/// Pin whose every operation fails.
+pub struct FailingPin;
+
+impl digital::ErrorType for FailingPin {
+    type Error = digital::ErrorKind;
+}
+
+impl OutputPin for FailingPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Err(digital::ErrorKind::Other)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Err(digital::ErrorKind::Other)
+    }
+}
+
+impl InputPin for FailingPin {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Err(digital::ErrorKind::Other)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Err(digital::ErrorKind::Other)
+    }
+}
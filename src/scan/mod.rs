@@ -1,13 +1,117 @@
-pub mod debounce;
-
+mod analog;
+mod anti_ghost;
+mod charlieplex;
 mod col2row;
+mod composite;
+mod config;
+mod debounce;
 mod direct;
+mod duplex;
+mod expander;
+mod idle;
 mod row2col;
+mod shift_register;
+mod transform;
+
+#[cfg(test)]
+pub(crate) mod mock;
+
+use core::convert::Infallible;
 
+use embedded_hal::{digital, spi};
+use embedded_hal_async::i2c;
+
+pub use analog::{
+    ActuationConfig, Adc, Analog, AnalogConfig, AnalogScan, Calibration, Ec, EcSense, FULL_TRAVEL,
+    Hc4051, Hc4051Sense,
+};
+pub use anti_ghost::AntiGhost;
+pub use charlieplex::{Charlieplex, DiodeOrientation};
 pub use col2row::Col2Row;
+pub use composite::Composite;
+pub use config::{MatrixConfig, Polarity};
+pub use debounce::{Debounce, DebounceAlgorithm, DebounceConfig, SharedDebounceConfig};
 pub use direct::Direct;
+pub use duplex::{Duplex, JapaneseDuplex};
+pub use expander::{Expander, ExpanderChip};
+pub use idle::Idle;
 pub use row2col::Row2Col;
+pub use shift_register::{
+    BitBangShiftIn, BitBangShiftOut, Hc165Col2Row, Hc595Col2Row, ShiftIn, ShiftOut, SpiShiftIn,
+    SpiShiftOut,
+};
+pub use transform::{Rotation, Transform};
 
 pub trait Scan<const W: usize, const H: usize> {
-    fn scan(&mut self, buf: &mut [[bool; W]; H]) -> impl core::future::Future<Output = ()>;
+    type Error: core::fmt::Debug;
+
+    /// Scans the matrix into `buf`. On failure, the contents of `buf` are unspecified and the
+    /// keyboard handles the error according to its [`ScanErrorPolicy`].
+    fn scan(
+        &mut self,
+        buf: &mut [[bool; W]; H],
+    ) -> impl core::future::Future<Output = Result<(), Self::Error>>;
+
+    /// Waits until any key might have been pressed, so the keyboard can stop scanning while idle.
+    ///
+    /// Returns `false` right away if the scanner is unable to wait, in which case the keyboard
+    /// keeps scanning every [`SCAN_INTERVAL`](crate::SCAN_INTERVAL).
+    fn wait_for_press(&mut self) -> impl core::future::Future<Output = bool> {
+        async { false }
+    }
+
+    /// Travel of the key at `x`, `y` in the last scan, from 0 at rest to [`FULL_TRAVEL`], if the
+    /// scanner reads analog keys.
+    fn travel(&self, _x: usize, _y: usize) -> Option<u8> {
+        None
+    }
+}
+
+/// Error of the built-in scanners.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScanError {
+    Pin(digital::ErrorKind),
+    Spi(spi::ErrorKind),
+    I2c(i2c::ErrorKind),
+    /// Reading an [`Adc`] failed.
+    Adc,
+    /// Receiving from the other half of a split keyboard failed.
+    Io(embedded_io_async::ErrorKind),
+}
+
+impl ScanError {
+    pub(crate) fn pin(error: impl digital::Error) -> Self {
+        Self::Pin(error.kind())
+    }
+
+    pub(crate) fn spi(error: impl spi::Error) -> Self {
+        Self::Spi(error.kind())
+    }
+
+    pub(crate) fn i2c(error: impl i2c::Error) -> Self {
+        Self::I2c(error.kind())
+    }
+
+    pub(crate) fn io(error: impl embedded_io_async::Error) -> Self {
+        Self::Io(error.kind())
+    }
+}
+
+impl From<Infallible> for ScanError {
+    fn from(error: Infallible) -> Self {
+        match error {}
+    }
+}
+
+/// How the keyboard handles a failed scan. Errors are always logged.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ScanErrorPolicy {
+    /// Keeps every key as it was in the last successful scan.
+    Keep,
+    /// Releases every key. Default.
+    #[default]
+    Release,
+    /// Scans again right away, up to the given number of times, before releasing every key.
+    Retry(u8),
 }
@@ -1,45 +1,98 @@
-use core::convert::Infallible;
-
-use embassy_time::Timer;
+use embassy_futures::select::select_array;
 use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal_async::digital::Wait;
 
-use super::{Scan, debounce::debounce};
+use super::{Debounce, Idle, MatrixConfig, Scan, ScanError, debounce::debounce};
 
 pub struct Row2Col<I, O, const W: usize, const H: usize> {
     rows: [O; H],
     cols: [I; W],
+    config: MatrixConfig,
 }
 
 impl<I, O, const W: usize, const H: usize> Row2Col<I, O, W, H>
 where
-    I: InputPin<Error = Infallible>,
-    O: OutputPin<Error = Infallible>,
+    I: InputPin,
+    O: OutputPin,
 {
     pub const fn new(rows: [O; H], cols: [I; W]) -> Self {
-        Self { rows, cols }
+        Self::with_config(rows, cols, MatrixConfig::new())
+    }
+
+    pub const fn with_config(rows: [O; H], cols: [I; W], config: MatrixConfig) -> Self {
+        Self { rows, cols, config }
     }
 
-    pub const fn debounced(rows: [O; H], cols: [I; W]) -> impl Scan<W, H> {
+    pub const fn debounced(rows: [O; H], cols: [I; W]) -> Debounce<Self, W, H> {
         debounce(Self::new(rows, cols))
     }
+
+    pub const fn debounced_with_config(
+        rows: [O; H],
+        cols: [I; W],
+        config: MatrixConfig,
+    ) -> Debounce<Self, W, H> {
+        debounce(Self::with_config(rows, cols, config))
+    }
+}
+
+impl<I, O, const W: usize, const H: usize> Row2Col<I, O, W, H>
+where
+    I: InputPin + Wait,
+    O: OutputPin,
+{
+    /// Wraps the scanner so the keyboard can wait for a key press instead of scanning while idle.
+    pub const fn idle(self) -> Idle<Self> {
+        Idle::new(self)
+    }
+
+    pub(super) async fn wait_for_any_col(&mut self) -> Result<(), ScanError> {
+        let config = self.config;
+
+        for pin in self.rows.iter_mut() {
+            config.select_line(pin).map_err(ScanError::pin)?;
+        }
+        config.wait_settle().await;
+
+        let (result, _) =
+            select_array(self.cols.each_mut().map(|pin| config.wait_for_active(pin))).await;
+
+        for pin in self.rows.iter_mut() {
+            config.unselect_line(pin).map_err(ScanError::pin)?;
+        }
+        config.wait_unselect().await;
+
+        result.map_err(ScanError::pin)
+    }
 }
 
 impl<I, O, const W: usize, const H: usize> Scan<W, H> for Row2Col<I, O, W, H>
 where
-    I: InputPin<Error = Infallible>,
-    O: OutputPin<Error = Infallible>,
+    I: InputPin,
+    O: OutputPin,
 {
-    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> () {
+    type Error = ScanError;
+
+    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> Result<(), ScanError> {
+        let config = self.config;
+
+        // Lines start out in an unknown state, and may be left selected by a failed scan.
+        for pin in self.rows.iter_mut() {
+            config.unselect_line(pin).map_err(ScanError::pin)?;
+        }
+
         for (y, row_pin) in self.rows.iter_mut().enumerate() {
-            row_pin.set_high().unwrap();
-            Timer::after_micros(30).await;
+            config.select_line(row_pin).map_err(ScanError::pin)?;
+            config.wait_settle().await;
 
             for (x, col_pin) in self.cols.iter_mut().enumerate() {
-                buf[y][x] = col_pin.is_high().unwrap();
+                buf[y][x] = config.is_active(col_pin).map_err(ScanError::pin)?;
             }
 
-            row_pin.set_low().unwrap();
-            Timer::after_micros(30).await;
+            config.unselect_line(row_pin).map_err(ScanError::pin)?;
+            config.wait_unselect().await;
         }
+
+        Ok(())
     }
 }
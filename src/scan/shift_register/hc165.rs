@@ -0,0 +1,232 @@
+use embedded_hal::digital::OutputPin;
+
+use crate::scan::{Debounce, MatrixConfig, Scan, ScanError, debounce::debounce};
+
+use super::{MAX_BYTES, ShiftIn};
+
+/// Scanner driving the columns with output pins and reading the rows through a chain of 74HC165
+/// shift registers.
+///
+/// Row `y` is input `H - (y % 8)`, i.e. the `y % 8`th bit shifted out, of the `y / 8`th register,
+/// counting from the MCU.
+pub struct Hc165Col2Row<O, T, const W: usize, const H: usize> {
+    cols: [O; W],
+    rows: T,
+    config: MatrixConfig,
+}
+
+impl<O, T, const W: usize, const H: usize> Hc165Col2Row<O, T, W, H>
+where
+    O: OutputPin,
+    T: ShiftIn,
+{
+    const _ASSERT: () = assert!(H <= MAX_BYTES * 8);
+
+    const BYTES: usize = H.div_ceil(8);
+
+    pub const fn new(cols: [O; W], rows: T) -> Self {
+        Self::with_config(cols, rows, MatrixConfig::new())
+    }
+
+    /// Creates the scanner with the given timing and polarity, where the input polarity applies
+    /// to the register inputs.
+    pub const fn with_config(cols: [O; W], rows: T, config: MatrixConfig) -> Self {
+        let () = Self::_ASSERT;
+        Self { cols, rows, config }
+    }
+
+    pub const fn debounced(cols: [O; W], rows: T) -> Debounce<Self, W, H> {
+        debounce(Self::new(cols, rows))
+    }
+
+    pub const fn debounced_with_config(
+        cols: [O; W],
+        rows: T,
+        config: MatrixConfig,
+    ) -> Debounce<Self, W, H> {
+        debounce(Self::with_config(cols, rows, config))
+    }
+}
+
+impl<O, T, const W: usize, const H: usize> Scan<W, H> for Hc165Col2Row<O, T, W, H>
+where
+    O: OutputPin,
+    T: ShiftIn,
+{
+    type Error = ScanError;
+
+    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> Result<(), ScanError> {
+        let config = self.config;
+        let mut data = [0; MAX_BYTES];
+        let data = &mut data[..Self::BYTES];
+
+        // Lines start out in an unknown state, and may be left selected by a failed scan.
+        for pin in self.cols.iter_mut() {
+            config.unselect_line(pin).map_err(ScanError::pin)?;
+        }
+
+        for (x, col_pin) in self.cols.iter_mut().enumerate() {
+            config.select_line(col_pin).map_err(ScanError::pin)?;
+            config.wait_settle().await;
+
+            self.rows.shift_in(data)?;
+            for (y, row) in buf.iter_mut().enumerate() {
+                row[x] = config.is_active_level(data[y / 8] & (0x80 >> (y % 8)) != 0);
+            }
+
+            config.unselect_line(col_pin).map_err(ScanError::pin)?;
+            config.wait_unselect().await;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use embassy_futures::block_on;
+    use embassy_time::Duration;
+
+    use crate::scan::{
+        mock::{MockInput, MockOutput, MockSpi},
+        shift_register::{BitBangShiftIn, SpiShiftIn},
+    };
+
+    use super::*;
+
+    const PRESSED: [[bool; 2]; 10] = [
+        [true, false],
+        [false, false],
+        [false, true],
+        [false, false],
+        [false, false],
+        [false, false],
+        [false, false],
+        [true, false],
+        [false, false],
+        [true, true],
+    ];
+
+    /// A chain of two 74HC165 whose inputs read the rows of `PRESSED`.
+    struct Hc165 {
+        cols: [Cell<bool>; 2],
+        shift: Cell<u16>,
+    }
+
+    impl Hc165 {
+        const fn new() -> Self {
+            Self {
+                cols: [Cell::new(false), Cell::new(false)],
+                shift: Cell::new(0),
+            }
+        }
+
+        /// Loads the rows so that row 0 is shifted out first.
+        fn load(&self) {
+            let mut shift = 0;
+            for (y, row) in PRESSED.iter().enumerate() {
+                if (0..2).any(|x| row[x] && self.cols[x].get()) {
+                    shift |= 0x8000 >> y;
+                }
+            }
+            self.shift.set(shift);
+        }
+
+        /// Loads the rows with columns selected low and rows pulled up.
+        fn load_active_low(&self) {
+            let mut shift = 0xFFFF;
+            for (y, row) in PRESSED.iter().enumerate() {
+                if (0..2).any(|x| row[x] && !self.cols[x].get()) {
+                    shift &= !(0x8000 >> y);
+                }
+            }
+            self.shift.set(shift);
+        }
+
+        fn data(&self) -> bool {
+            self.shift.get() & 0x8000 != 0
+        }
+
+        fn clock(&self) {
+            self.shift.set(self.shift.get() << 1);
+        }
+
+        fn shift_byte(&self) -> u8 {
+            let byte = (self.shift.get() >> 8) as u8;
+            self.shift.set(self.shift.get() << 8);
+            byte
+        }
+    }
+
+    #[test]
+    fn scan_over_spi() {
+        let hc165 = Hc165::new();
+        let spi = MockSpi::new(|_| {}, || hc165.shift_byte());
+        let load = MockOutput::new(|high| {
+            if !high {
+                hc165.load()
+            }
+        });
+        let cols = hc165
+            .cols
+            .each_ref()
+            .map(|col| MockOutput::new(|high| col.set(high)));
+
+        let mut scanner = Hc165Col2Row::new(cols, SpiShiftIn::new(spi, load));
+        let mut buf = [[false; 2]; 10];
+        block_on(scanner.scan(&mut buf)).unwrap();
+
+        assert_eq!(buf, PRESSED);
+    }
+
+    #[test]
+    fn scan_active_low() {
+        let hc165 = Hc165::new();
+        let spi = MockSpi::new(|_| {}, || hc165.shift_byte());
+        let load = MockOutput::new(|high| {
+            if !high {
+                hc165.load_active_low()
+            }
+        });
+        let cols = hc165
+            .cols
+            .each_ref()
+            .map(|col| MockOutput::new(|high| col.set(high)));
+
+        let config = MatrixConfig::active_low().unselect(Duration::MIN);
+        let mut scanner = Hc165Col2Row::with_config(cols, SpiShiftIn::new(spi, load), config);
+        let mut buf = [[false; 2]; 10];
+        block_on(scanner.scan(&mut buf)).unwrap();
+
+        assert_eq!(buf, PRESSED);
+        assert!(hc165.cols.iter().all(Cell::get));
+    }
+
+    #[test]
+    fn scan_over_bit_bang() {
+        let hc165 = Hc165::new();
+        let data = MockInput::new(|| hc165.data());
+        let clock = MockOutput::new(|high| {
+            if high {
+                hc165.clock()
+            }
+        });
+        let load = MockOutput::new(|high| {
+            if !high {
+                hc165.load()
+            }
+        });
+        let cols = hc165
+            .cols
+            .each_ref()
+            .map(|col| MockOutput::new(|high| col.set(high)));
+
+        let mut scanner = Hc165Col2Row::new(cols, BitBangShiftIn::new(data, clock, load));
+        let mut buf = [[false; 2]; 10];
+        block_on(scanner.scan(&mut buf)).unwrap();
+
+        assert_eq!(buf, PRESSED);
+    }
+}
@@ -0,0 +1,215 @@
+use embedded_hal::digital::InputPin;
+
+use crate::scan::{Debounce, MatrixConfig, Scan, ScanError, debounce::debounce};
+
+use super::{MAX_BYTES, ShiftOut};
+
+/// Scanner driving the columns through a chain of 74HC595 shift registers and reading the rows
+/// with input pins.
+///
+/// Column `x` is output `Q(x % 8)` of the `x / 8`th register, counting from the MCU.
+pub struct Hc595Col2Row<T, I, const W: usize, const H: usize> {
+    cols: T,
+    rows: [I; H],
+    config: MatrixConfig,
+}
+
+impl<T, I, const W: usize, const H: usize> Hc595Col2Row<T, I, W, H>
+where
+    T: ShiftOut,
+    I: InputPin,
+{
+    const _ASSERT: () = assert!(W <= MAX_BYTES * 8);
+
+    const BYTES: usize = W.div_ceil(8);
+
+    pub const fn new(cols: T, rows: [I; H]) -> Self {
+        Self::with_config(cols, rows, MatrixConfig::new())
+    }
+
+    /// Creates the scanner with the given timing and polarity, where the drive polarity applies
+    /// to the register outputs.
+    pub const fn with_config(cols: T, rows: [I; H], config: MatrixConfig) -> Self {
+        let () = Self::_ASSERT;
+        Self { cols, rows, config }
+    }
+
+    pub const fn debounced(cols: T, rows: [I; H]) -> Debounce<Self, W, H> {
+        debounce(Self::new(cols, rows))
+    }
+
+    pub const fn debounced_with_config(
+        cols: T,
+        rows: [I; H],
+        config: MatrixConfig,
+    ) -> Debounce<Self, W, H> {
+        debounce(Self::with_config(cols, rows, config))
+    }
+
+    fn select(&mut self, col: Option<usize>) -> Result<(), ScanError> {
+        let unselected = if self.config.line_level(false) {
+            0xFF
+        } else {
+            0
+        };
+        let mut data = [unselected; MAX_BYTES];
+        let data = &mut data[..Self::BYTES];
+
+        if let Some(x) = col {
+            data[Self::BYTES - 1 - x / 8] ^= 1 << (x % 8);
+        }
+
+        self.cols.shift_out(data)
+    }
+}
+
+impl<T, I, const W: usize, const H: usize> Scan<W, H> for Hc595Col2Row<T, I, W, H>
+where
+    T: ShiftOut,
+    I: InputPin,
+{
+    type Error = ScanError;
+
+    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> Result<(), ScanError> {
+        let config = self.config;
+
+        for x in 0..W {
+            self.select(Some(x))?;
+            config.wait_settle().await;
+
+            for (row, row_pin) in buf.iter_mut().zip(self.rows.iter_mut()) {
+                row[x] = config.is_active(row_pin).map_err(ScanError::pin)?;
+            }
+
+            self.select(None)?;
+            config.wait_unselect().await;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use embassy_futures::block_on;
+    use embassy_time::Duration;
+    use embedded_hal::{digital::OutputPin, spi::SpiBus};
+
+    use crate::scan::{
+        mock::{MockInput, MockOutput, MockSpi},
+        shift_register::{BitBangShiftOut, SpiShiftOut},
+    };
+
+    use super::*;
+
+    const PRESSED: [[bool; 10]; 2] = [
+        [
+            true, false, false, false, false, false, false, false, false, true,
+        ],
+        [
+            false, true, false, false, false, false, false, false, true, false,
+        ],
+    ];
+
+    /// A chain of two 74HC595 whose outputs drive the columns of `PRESSED`.
+    struct Hc595 {
+        shift: Cell<u16>,
+        outputs: Cell<u16>,
+    }
+
+    impl Hc595 {
+        const fn new() -> Self {
+            Self {
+                shift: Cell::new(0),
+                outputs: Cell::new(0),
+            }
+        }
+
+        fn clock_in(&self, bit: bool) {
+            self.shift.set((self.shift.get() << 1) | bit as u16);
+        }
+
+        fn latch(&self) {
+            self.outputs.set(self.shift.get());
+        }
+
+        fn row(&self, y: usize) -> bool {
+            (0..10).any(|x| PRESSED[y][x] && self.outputs.get() & (1 << x) != 0)
+        }
+
+        /// Level of row `y` with columns selected low and rows pulled up.
+        fn row_active_low(&self, y: usize) -> bool {
+            !(0..10).any(|x| PRESSED[y][x] && self.outputs.get() & (1 << x) == 0)
+        }
+    }
+
+    fn spi(hc595: &Hc595) -> SpiShiftOut<impl SpiBus, impl OutputPin> {
+        let spi = MockSpi::new(
+            |byte| {
+                (0..8)
+                    .rev()
+                    .for_each(|i| hc595.clock_in(byte & (1 << i) != 0))
+            },
+            || 0,
+        );
+        let latch = MockOutput::new(|high| {
+            if high {
+                hc595.latch()
+            }
+        });
+        SpiShiftOut::new(spi, latch)
+    }
+
+    #[test]
+    fn scan_over_spi() {
+        let hc595 = &Hc595::new();
+        let rows = [0, 1].map(|y| MockInput::new(move || hc595.row(y)));
+
+        let mut scanner = Hc595Col2Row::new(spi(hc595), rows);
+        let mut buf = [[false; 10]; 2];
+        block_on(scanner.scan(&mut buf)).unwrap();
+
+        assert_eq!(buf, PRESSED);
+        assert_eq!(hc595.outputs.get(), 0);
+    }
+
+    #[test]
+    fn scan_active_low() {
+        let hc595 = &Hc595::new();
+        let rows = [0, 1].map(|y| MockInput::new(move || hc595.row_active_low(y)));
+
+        let config = MatrixConfig::active_low().unselect(Duration::MIN);
+        let mut scanner = Hc595Col2Row::with_config(spi(hc595), rows, config);
+        let mut buf = [[false; 10]; 2];
+        block_on(scanner.scan(&mut buf)).unwrap();
+
+        assert_eq!(buf, PRESSED);
+        assert_eq!(hc595.outputs.get(), 0xFFFF);
+    }
+
+    #[test]
+    fn scan_over_bit_bang() {
+        let hc595 = &Hc595::new();
+        let data = Cell::new(false);
+        let data_pin = MockOutput::new(|high| data.set(high));
+        let clock = MockOutput::new(|high| {
+            if high {
+                hc595.clock_in(data.get())
+            }
+        });
+        let latch = MockOutput::new(|high| {
+            if high {
+                hc595.latch()
+            }
+        });
+        let rows = [0, 1].map(|y| MockInput::new(move || hc595.row(y)));
+
+        let mut scanner = Hc595Col2Row::new(BitBangShiftOut::new(data_pin, clock, latch), rows);
+        let mut buf = [[false; 10]; 2];
+        block_on(scanner.scan(&mut buf)).unwrap();
+
+        assert_eq!(buf, PRESSED);
+    }
+}
@@ -0,0 +1,170 @@
+//! Matrix scanners for boards that drive columns through 74HC595 or read rows through 74HC165
+//! shift registers, over SPI or bit-banged pins.
+
+mod hc165;
+mod hc595;
+
+use embedded_hal::{
+    digital::{InputPin, OutputPin},
+    spi::SpiBus,
+};
+
+use super::ScanError;
+
+pub use hc165::Hc165Col2Row;
+pub use hc595::Hc595Col2Row;
+
+/// Maximum number of bytes, and so chained registers, that can be shifted at once.
+const MAX_BYTES: usize = 4;
+
+/// Writes bytes into a chain of serial-in parallel-out registers, like the 74HC595.
+pub trait ShiftOut {
+    /// Shifts out `data` most significant bit first and latches it to the outputs. The last byte
+    /// ends up in the register closest to the MCU.
+    fn shift_out(&mut self, data: &[u8]) -> Result<(), ScanError>;
+}
+
+/// Reads bytes from a chain of parallel-in serial-out registers, like the 74HC165.
+pub trait ShiftIn {
+    /// Loads the parallel inputs and shifts them into `buf` most significant bit first. The first
+    /// byte comes from the register closest to the MCU.
+    fn shift_in(&mut self, buf: &mut [u8]) -> Result<(), ScanError>;
+}
+
+/// [`ShiftOut`] over an SPI bus, with a separate latch (`RCLK`) pin.
+pub struct SpiShiftOut<S, L> {
+    spi: S,
+    latch: L,
+}
+
+impl<S, L> SpiShiftOut<S, L>
+where
+    S: SpiBus,
+    L: OutputPin,
+{
+    pub const fn new(spi: S, latch: L) -> Self {
+        Self { spi, latch }
+    }
+}
+
+impl<S, L> ShiftOut for SpiShiftOut<S, L>
+where
+    S: SpiBus,
+    L: OutputPin,
+{
+    fn shift_out(&mut self, data: &[u8]) -> Result<(), ScanError> {
+        self.spi.write(data).map_err(ScanError::spi)?;
+        self.spi.flush().map_err(ScanError::spi)?;
+        self.latch.set_high().map_err(ScanError::pin)?;
+        self.latch.set_low().map_err(ScanError::pin)
+    }
+}
+
+/// [`ShiftOut`] over bit-banged data (`SER`), clock (`SRCLK`) and latch (`RCLK`) pins.
+pub struct BitBangShiftOut<D, C, L> {
+    data: D,
+    clock: C,
+    latch: L,
+}
+
+impl<D, C, L> BitBangShiftOut<D, C, L>
+where
+    D: OutputPin,
+    C: OutputPin,
+    L: OutputPin,
+{
+    pub const fn new(data: D, clock: C, latch: L) -> Self {
+        Self { data, clock, latch }
+    }
+}
+
+impl<D, C, L> ShiftOut for BitBangShiftOut<D, C, L>
+where
+    D: OutputPin,
+    C: OutputPin,
+    L: OutputPin,
+{
+    fn shift_out(&mut self, data: &[u8]) -> Result<(), ScanError> {
+        for byte in data {
+            for i in (0..8).rev() {
+                self.data
+                    .set_state((byte & (1 << i) != 0).into())
+                    .map_err(ScanError::pin)?;
+                self.clock.set_high().map_err(ScanError::pin)?;
+                self.clock.set_low().map_err(ScanError::pin)?;
+            }
+        }
+
+        self.latch.set_high().map_err(ScanError::pin)?;
+        self.latch.set_low().map_err(ScanError::pin)
+    }
+}
+
+/// [`ShiftIn`] over an SPI bus, with a separate active-low load (`SH/LD`) pin.
+pub struct SpiShiftIn<S, L> {
+    spi: S,
+    load: L,
+}
+
+impl<S, L> SpiShiftIn<S, L>
+where
+    S: SpiBus,
+    L: OutputPin,
+{
+    pub const fn new(spi: S, load: L) -> Self {
+        Self { spi, load }
+    }
+}
+
+impl<S, L> ShiftIn for SpiShiftIn<S, L>
+where
+    S: SpiBus,
+    L: OutputPin,
+{
+    fn shift_in(&mut self, buf: &mut [u8]) -> Result<(), ScanError> {
+        self.load.set_low().map_err(ScanError::pin)?;
+        self.load.set_high().map_err(ScanError::pin)?;
+        self.spi.read(buf).map_err(ScanError::spi)
+    }
+}
+
+/// [`ShiftIn`] over bit-banged data (`QH`), clock (`CLK`) and active-low load (`SH/LD`) pins.
+pub struct BitBangShiftIn<D, C, L> {
+    data: D,
+    clock: C,
+    load: L,
+}
+
+impl<D, C, L> BitBangShiftIn<D, C, L>
+where
+    D: InputPin,
+    C: OutputPin,
+    L: OutputPin,
+{
+    pub const fn new(data: D, clock: C, load: L) -> Self {
+        Self { data, clock, load }
+    }
+}
+
+impl<D, C, L> ShiftIn for BitBangShiftIn<D, C, L>
+where
+    D: InputPin,
+    C: OutputPin,
+    L: OutputPin,
+{
+    fn shift_in(&mut self, buf: &mut [u8]) -> Result<(), ScanError> {
+        self.load.set_low().map_err(ScanError::pin)?;
+        self.load.set_high().map_err(ScanError::pin)?;
+
+        for byte in buf.iter_mut() {
+            *byte = 0;
+            for i in (0..8).rev() {
+                *byte |= (self.data.is_high().map_err(ScanError::pin)? as u8) << i;
+                self.clock.set_high().map_err(ScanError::pin)?;
+                self.clock.set_low().map_err(ScanError::pin)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+use core::ops::Range;
+
+use super::Scan;
+
+/// Rotation of a matrix, clockwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Rotation {
+    Quarter,
+    Half,
+    ThreeQuarters,
+}
+
+/// Scanner moving every key from its position in the electrical matrix, `MW` by `MH`, to its
+/// position in the layout, `W` by `H`, so keymaps are written in the order the keys are laid
+/// out.
+///
+/// Keys can be mirrored, for example the right half of a split keyboard wired as the mirror image
+/// of the left one, rotated, or remapped one by one. Layout positions no key moves to read as
+/// released.
+pub struct Transform<S, const MW: usize, const MH: usize, const W: usize, const H: usize> {
+    scanner: S,
+    /// Matrix position of every layout position.
+    inverse: [[Option<(usize, usize)>; W]; H],
+    buf: [[bool; MW]; MH],
+}
+
+impl<S, const MW: usize, const MH: usize, const W: usize, const H: usize>
+    Transform<S, MW, MH, W, H>
+{
+    /// Moves each key to the given `(x, y)` layout position, or drops it if `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a position is outside the layout, or if two keys move to the same position.
+    pub fn remapped(scanner: S, map: [[Option<(usize, usize)>; MW]; MH]) -> Self {
+        let mut inverse = [[None; W]; H];
+        for (y, row) in map.iter().enumerate() {
+            for (x, &position) in row.iter().enumerate() {
+                if let Some((lx, ly)) = position {
+                    assert!(inverse[ly][lx].is_none(), "two keys at ({}, {})", lx, ly);
+                    inverse[ly][lx] = Some((x, y));
+                }
+            }
+        }
+
+        Self {
+            scanner,
+            inverse,
+            buf: [[false; MW]; MH],
+        }
+    }
+
+    /// Mirrors the whole matrix from left to right.
+    pub fn mirrored(scanner: S) -> Self {
+        Self::mirrored_columns(scanner, 0..MW)
+    }
+
+    /// Mirrors the given columns from left to right, leaving the others in place, for example
+    /// the columns of the right half of a split keyboard.
+    pub fn mirrored_columns(scanner: S, columns: Range<usize>) -> Self {
+        assert!(MW == W && MH == H && columns.end <= MW);
+
+        Self::from_fn(scanner, |x, y| {
+            if columns.contains(&x) {
+                (columns.start + columns.end - 1 - x, y)
+            } else {
+                (x, y)
+            }
+        })
+    }
+
+    /// Rotates the whole matrix, which must be `H` by `W` for quarter turns.
+    pub fn rotated(scanner: S, rotation: Rotation) -> Self {
+        match rotation {
+            Rotation::Half => assert!(MW == W && MH == H),
+            Rotation::Quarter | Rotation::ThreeQuarters => assert!(MW == H && MH == W),
+        }
+
+        Self::from_fn(scanner, |x, y| match rotation {
+            Rotation::Quarter => (MH - 1 - y, x),
+            Rotation::Half => (MW - 1 - x, MH - 1 - y),
+            Rotation::ThreeQuarters => (y, MW - 1 - x),
+        })
+    }
+
+    fn from_fn(scanner: S, f: impl Fn(usize, usize) -> (usize, usize)) -> Self {
+        let mut map = [[None; MW]; MH];
+        for (y, row) in map.iter_mut().enumerate() {
+            for (x, position) in row.iter_mut().enumerate() {
+                *position = Some(f(x, y));
+            }
+        }
+
+        Self::remapped(scanner, map)
+    }
+}
+
+impl<S, const MW: usize, const MH: usize, const W: usize, const H: usize> Scan<W, H>
+    for Transform<S, MW, MH, W, H>
+where
+    S: Scan<MW, MH>,
+{
+    type Error = S::Error;
+
+    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> Result<(), S::Error> {
+        self.scanner.scan(&mut self.buf).await?;
+
+        for (row, positions) in buf.iter_mut().zip(&self.inverse) {
+            for (pressed, position) in row.iter_mut().zip(positions) {
+                *pressed = position.is_some_and(|(x, y)| self.buf[y][x]);
+            }
+        }
+        Ok(())
+    }
+
+    async fn wait_for_press(&mut self) -> bool {
+        self.scanner.wait_for_press().await
+    }
+
+    fn travel(&self, x: usize, y: usize) -> Option<u8> {
+        let (x, y) = self.inverse[y][x]?;
+        self.scanner.travel(x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use crate::scan::{
+        FULL_TRAVEL,
+        mock::{MockMatrix, scan},
+    };
+
+    use super::*;
+
+    #[test]
+    fn mirrors_columns() {
+        let keys = Cell::new([[true, false, true, true, false], [false; 5]]);
+        let mut scanner =
+            Transform::<_, 5, 2, 5, 2>::mirrored_columns(MockMatrix::new(&keys).analog(), 2..5);
+
+        assert_eq!(
+            scan(&mut scanner),
+            [[true, false, false, true, true], [false; 5]]
+        );
+        assert_eq!(scanner.travel(2, 0), Some(0));
+        assert_eq!(scanner.travel(4, 0), Some(FULL_TRAVEL));
+    }
+
+    #[test]
+    fn rotates_quarter_turns() {
+        let keys = Cell::new([[true, true, false], [false; 3]]);
+
+        let mut quarter =
+            Transform::<_, 3, 2, 2, 3>::rotated(MockMatrix::new(&keys), Rotation::Quarter);
+        assert_eq!(
+            scan(&mut quarter),
+            [[false, true], [false, true], [false, false]]
+        );
+
+        let mut three_quarters =
+            Transform::<_, 3, 2, 2, 3>::rotated(MockMatrix::new(&keys), Rotation::ThreeQuarters);
+        assert_eq!(
+            scan(&mut three_quarters),
+            [[false, false], [true, false], [true, false]]
+        );
+    }
+
+    #[test]
+    fn drops_unmapped_keys() {
+        let keys = Cell::new([[true, false, true]]);
+        let mut scanner = Transform::<_, 3, 1, 2, 2>::remapped(
+            MockMatrix::new(&keys).analog(),
+            [[Some((1, 1)), None, Some((0, 0))]],
+        );
+
+        assert_eq!(scan(&mut scanner), [[true, false], [false, true]]);
+        assert_eq!(scanner.travel(1, 0), None);
+        assert_eq!(scanner.travel(1, 1), Some(FULL_TRAVEL));
+    }
+}
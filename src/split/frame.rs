@@ -0,0 +1,196 @@
+//! HDLC-style framing: every frame is delimited by [`FLAG`] bytes, with flag and escape bytes
+//! inside it escaped. A receiver that loses track, for example after noise on the line, drops
+//! everything up to the next flag and carries on from there.
+//!
+//! The content of a frame is a sequence number, the kind of message, its payload, and a CRC-8 of
+//! everything before it.
+
+pub const FLAG: u8 = 0x7E;
+const ESCAPE: u8 = 0x7D;
+const ESCAPE_XOR: u8 = 0x20;
+
+/// Largest payload of a frame.
+pub const MAX_PAYLOAD: usize = 32;
+
+/// Sequence number, kind, payload and CRC.
+const MAX_CONTENT: usize = MAX_PAYLOAD + 3;
+
+/// Longest encoded frame, with every byte escaped.
+pub const MAX_FRAME: usize = 2 * MAX_CONTENT + 2;
+
+/// A received frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frame {
+    pub seq: u8,
+    pub kind: u8,
+    len: usize,
+    payload: [u8; MAX_PAYLOAD],
+}
+
+impl Frame {
+    pub fn payload(&self) -> &[u8] {
+        &self.payload[..self.len]
+    }
+}
+
+/// CRC-8 with polynomial 0x07.
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0, |crc, &byte| {
+        (0..8).fold(crc ^ byte, |crc, _| {
+            if crc & 0x80 != 0 {
+                crc << 1 ^ 0x07
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+/// Encodes a frame into `buf`, returning its length.
+pub fn encode(seq: u8, kind: u8, payload: &[u8], buf: &mut [u8; MAX_FRAME]) -> usize {
+    let mut content = [0; MAX_CONTENT];
+    let len = payload.len() + 2;
+    content[0] = seq;
+    content[1] = kind;
+    content[2..len].copy_from_slice(payload);
+    content[len] = crc8(&content[..len]);
+
+    let mut i = 0;
+    buf[i] = FLAG;
+    i += 1;
+
+    for &byte in &content[..len + 1] {
+        if byte == FLAG || byte == ESCAPE {
+            buf[i] = ESCAPE;
+            buf[i + 1] = byte ^ ESCAPE_XOR;
+            i += 2;
+        } else {
+            buf[i] = byte;
+            i += 1;
+        }
+    }
+
+    buf[i] = FLAG;
+    i + 1
+}
+
+/// Reassembles frames from received bytes.
+pub struct Decoder {
+    content: [u8; MAX_CONTENT],
+    len: usize,
+    is_escaped: bool,
+    /// Whether the current frame is to be dropped, because it overflowed or was not started with
+    /// a flag.
+    is_dropping: bool,
+}
+
+impl Decoder {
+    pub const fn new() -> Self {
+        Self {
+            content: [0; MAX_CONTENT],
+            len: 0,
+            is_escaped: false,
+            is_dropping: true,
+        }
+    }
+
+    /// Feeds the next received byte, returning a frame if it completes a valid one.
+    pub fn push(&mut self, byte: u8) -> Option<Frame> {
+        if byte == FLAG {
+            let frame = if self.is_dropping { None } else { self.frame() };
+
+            self.len = 0;
+            self.is_escaped = false;
+            self.is_dropping = false;
+            return frame;
+        }
+
+        if self.is_dropping {
+            return None;
+        }
+
+        let byte = if self.is_escaped {
+            self.is_escaped = false;
+            byte ^ ESCAPE_XOR
+        } else if byte == ESCAPE {
+            self.is_escaped = true;
+            return None;
+        } else {
+            byte
+        };
+
+        if self.len == MAX_CONTENT {
+            self.is_dropping = true;
+            return None;
+        }
+
+        self.content[self.len] = byte;
+        self.len += 1;
+        None
+    }
+
+    fn frame(&self) -> Option<Frame> {
+        // Back-to-back flags delimit empty frames, which are just skipped.
+        if self.len < 3 {
+            return None;
+        }
+
+        let (content, crc) = self.content[..self.len].split_at(self.len - 1);
+        if crc8(content) != crc[0] {
+            return None;
+        }
+
+        let mut frame = Frame {
+            seq: content[0],
+            kind: content[1],
+            len: content.len() - 2,
+            payload: [0; MAX_PAYLOAD],
+        };
+        frame.payload[..frame.len].copy_from_slice(&content[2..]);
+        Some(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(decoder: &mut Decoder, bytes: &[u8]) -> Option<Frame> {
+        bytes
+            .iter()
+            .fold(None, |frame, &byte| decoder.push(byte).or(frame))
+    }
+
+    #[test]
+    fn round_trip_with_escapes() {
+        let mut buf = [0; MAX_FRAME];
+        let len = encode(FLAG, ESCAPE, &[1, FLAG, ESCAPE, 2], &mut buf);
+
+        let frame = decode(&mut Decoder::new(), &buf[..len]).unwrap();
+        assert_eq!((frame.seq, frame.kind), (FLAG, ESCAPE));
+        assert_eq!(frame.payload(), &[1, FLAG, ESCAPE, 2]);
+    }
+
+    #[test]
+    fn resyncs_after_noise() {
+        let mut buf = [0; MAX_FRAME];
+        let len = encode(1, 2, &[3], &mut buf);
+        let mut decoder = Decoder::new();
+
+        // Noise before the first flag, and a frame cut short by it.
+        assert_eq!(decode(&mut decoder, &[0x12, 0x34]), None);
+        assert_eq!(decode(&mut decoder, &buf[..3]), None);
+
+        let frame = decode(&mut decoder, &buf[..len]).unwrap();
+        assert_eq!(frame.payload(), &[3]);
+    }
+
+    #[test]
+    fn drops_corrupted_frames() {
+        let mut buf = [0; MAX_FRAME];
+        let len = encode(1, 2, &[3, 4], &mut buf);
+        buf[3] ^= 0x01;
+
+        assert_eq!(decode(&mut Decoder::new(), &buf[..len]), None);
+    }
+}
@@ -0,0 +1,184 @@
+use embassy_futures::{
+    join::join,
+    select::{Either, select},
+};
+use embedded_io_async::{Read, ReadReady, Write};
+
+use crate::{
+    info,
+    scan::{Scan, ScanError},
+};
+
+use super::{Handedness, Remote, Role, Secondary, SplitState, role::DetectRole};
+
+/// Scanner for either half of a split keyboard, taking the role its [`DetectRole`] tells, so a
+/// single firmware runs on both halves.
+///
+/// As the primary half, it places the keys of this half and of the other one, received with a
+/// [`Remote`], side by side according to the [`Handedness`] of this half. The matrix is `W` wide,
+/// twice the `SW` columns of each half. As the secondary half, it sends the keys of this half
+/// with a [`Secondary`], and scans as every key released, as the keyboard is not connected to a
+/// host anyway.
+///
+/// The role is detected again on every scan, so the halves swap roles if the other one gets
+/// connected instead. While idle as the primary half, the keyboard waits for a press on either
+/// half, if the scanner of this half is able to wait.
+pub struct Half<S, T, R, const SW: usize, const W: usize, const H: usize> {
+    link: Link<S, T, SW, H>,
+    detector: R,
+    /// Default: [`Handedness::Left`].
+    handedness: Handedness,
+    /// State shared with the other half. Default: none.
+    sync: Option<&'static SplitState>,
+}
+
+enum Link<S, T, const SW: usize, const H: usize> {
+    Primary {
+        local: S,
+        remote: Remote<T, SW, H>,
+    },
+    Secondary(Secondary<S, T, SW, H>),
+    /// Only while switching roles.
+    None,
+}
+
+impl<S, T, R, const SW: usize, const W: usize, const H: usize> Half<S, T, R, SW, W, H>
+where
+    S: Scan<SW, H>,
+    S::Error: Into<ScanError>,
+    T: Read + ReadReady + Write,
+    R: DetectRole,
+{
+    const _ASSERT: () = assert!(W == 2 * SW);
+
+    /// Creates the scanner, starting as the secondary half until the role is first detected.
+    pub const fn new(scanner: S, transport: T, detector: R) -> Self {
+        let () = Self::_ASSERT;
+        Self {
+            link: Link::Secondary(Secondary::new(scanner, transport)),
+            detector,
+            handedness: Handedness::Left,
+            sync: None,
+        }
+    }
+
+    pub const fn handedness(mut self, handedness: Handedness) -> Self {
+        self.handedness = handedness;
+        self
+    }
+
+    pub const fn sync(mut self, state: &'static SplitState) -> Self {
+        self.sync = Some(state);
+        self
+    }
+
+    fn role(&self) -> Role {
+        match self.link {
+            Link::Primary { .. } => Role::Primary,
+            Link::Secondary(_) | Link::None => Role::Secondary,
+        }
+    }
+
+    fn switch(&mut self, role: Role) {
+        info!("Taking the {} role", role);
+
+        let (scanner, transport) = match core::mem::replace(&mut self.link, Link::None) {
+            Link::Primary { local, remote } => (local, remote.into_transport()),
+            Link::Secondary(secondary) => secondary.into_parts(),
+            Link::None => unreachable!(),
+        };
+
+        self.link = match role {
+            Role::Primary => {
+                let mut remote = Remote::new(transport);
+                if let Some(sync) = self.sync {
+                    remote = remote.sync(sync);
+                }
+                Link::Primary {
+                    local: scanner,
+                    remote,
+                }
+            }
+            Role::Secondary => {
+                let mut secondary = Secondary::new(scanner, transport);
+                if let Some(sync) = self.sync {
+                    secondary = secondary.sync(sync);
+                }
+                Link::Secondary(secondary)
+            }
+        };
+    }
+}
+
+impl<S, T, R, const SW: usize, const W: usize, const H: usize> Scan<W, H>
+    for Half<S, T, R, SW, W, H>
+where
+    S: Scan<SW, H>,
+    S::Error: Into<ScanError>,
+    T: Read + ReadReady + Write,
+    R: DetectRole,
+{
+    type Error = ScanError;
+
+    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> Result<(), ScanError> {
+        let role = self.detector.role()?;
+        if role != self.role() {
+            self.switch(role);
+        }
+
+        match &mut self.link {
+            Link::Primary { local, remote } => {
+                let mut local_keys = [[false; SW]; H];
+                let mut remote_keys = [[false; SW]; H];
+                let (local_result, remote_result) =
+                    join(local.scan(&mut local_keys), remote.scan(&mut remote_keys)).await;
+                local_result.map_err(Into::into)?;
+                remote_result?;
+
+                let (left, right) = match self.handedness {
+                    Handedness::Left => (local_keys, remote_keys),
+                    Handedness::Right => (remote_keys, local_keys),
+                };
+                for (row, (left, right)) in buf.iter_mut().zip(left.iter().zip(&right)) {
+                    row[..SW].copy_from_slice(left);
+                    row[SW..].copy_from_slice(right);
+                }
+            }
+            Link::Secondary(secondary) => {
+                secondary.poll().await.map_err(ScanError::io)?;
+                *buf = [[false; W]; H];
+            }
+            Link::None => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    async fn wait_for_press(&mut self) -> bool {
+        match &mut self.link {
+            Link::Primary { local, remote } => {
+                match select(local.wait_for_press(), remote.wait_for_press()).await {
+                    Either::First(woke) | Either::Second(woke) => woke,
+                }
+            }
+            // The secondary half keeps scanning to send its keys.
+            Link::Secondary(_) | Link::None => false,
+        }
+    }
+
+    fn travel(&self, x: usize, y: usize) -> Option<u8> {
+        let Link::Primary { local, remote } = &self.link else {
+            return None;
+        };
+
+        let (handedness, x) = match x.checked_sub(SW) {
+            Some(x) => (Handedness::Right, x),
+            None => (Handedness::Left, x),
+        };
+        if handedness == self.handedness {
+            local.travel(x, y)
+        } else {
+            remote.travel(x, y)
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use embedded_io_async::{Read, ReadReady, Write};
+
+use crate::{trace, warn};
+
+use super::{
+    Role,
+    frame::{self, Decoder, MAX_FRAME, MAX_PAYLOAD},
+    message::Message,
+};
+
+/// One end of the serial link between the halves of a split keyboard.
+pub struct Link<T> {
+    transport: T,
+    /// Role of this end, whose own messages are dropped, as half-duplex links echo them back.
+    role: Role,
+    /// Sequence number of the next frame sent.
+    seq: u8,
+    /// Sequence number of the last frame received.
+    last_seq: Option<u8>,
+    decoder: Decoder,
+    /// Bytes read while waiting for the other half, decoded on the next receive.
+    pending: [u8; MAX_FRAME],
+    pending_len: usize,
+}
+
+impl<T> Link<T> {
+    pub const fn new(transport: T, role: Role) -> Self {
+        Self {
+            transport,
+            role,
+            seq: 0,
+            last_seq: None,
+            decoder: Decoder::new(),
+            pending: [0; MAX_FRAME],
+            pending_len: 0,
+        }
+    }
+
+    pub fn into_transport(self) -> T {
+        self.transport
+    }
+
+    /// Forgets the sequence number of the last frame received, so the next frame is accepted
+    /// whatever its sequence number, e.g. after the other half restarted.
+    pub fn resync(&mut self) {
+        self.last_seq = None;
+    }
+
+    /// Whether a frame with `seq` is not a duplicate of the last one received.
+    fn is_new(&mut self, seq: u8) -> bool {
+        let Some(last_seq) = self.last_seq.replace(seq) else {
+            return true;
+        };
+
+        match seq.wrapping_sub(last_seq) {
+            0 => false,
+            1 => true,
+            _gap => {
+                warn!("Lost {} frames from the other half", _gap - 1);
+                true
+            }
+        }
+    }
+}
+
+impl<T: Write> Link<T> {
+    pub async fn send(&mut self, message: Message<'_>) -> Result<(), T::Error> {
+        trace!(
+            "Sending to the other half: {}",
+            defmt::Debug2Format(&message)
+        );
+        let mut payload = [0; MAX_PAYLOAD];
+        let (kind, payload) = message.encode(&mut payload);
+
+        let mut buf = [0; MAX_FRAME];
+        let len = frame::encode(self.seq, kind, payload, &mut buf);
+        self.seq = self.seq.wrapping_add(1);
+
+        self.transport.write_all(&buf[..len]).await
+    }
+}
+
+impl<T: Read> Link<T> {
+    /// Waits until the other half sends anything, keeping it for the next receive.
+    pub async fn wait(&mut self) -> Result<(), T::Error> {
+        if self.pending_len == 0 {
+            self.pending_len = self.transport.read(&mut self.pending).await?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Read + ReadReady> Link<T> {
+    /// Receives every message already waiting, without waiting for more.
+    pub async fn receive(&mut self, mut on_message: impl FnMut(Message)) -> Result<(), T::Error> {
+        let pending = self.pending;
+        let len = core::mem::take(&mut self.pending_len);
+        self.decode(&pending[..len], &mut on_message);
+
+        let mut buf = [0; MAX_FRAME];
+        while self.transport.read_ready()? {
+            let len = self.transport.read(&mut buf).await?;
+            if len == 0 {
+                break;
+            }
+            self.decode(&buf[..len], &mut on_message);
+        }
+
+        Ok(())
+    }
+
+    fn decode(&mut self, bytes: &[u8], on_message: &mut impl FnMut(Message)) {
+        for &byte in bytes {
+            let Some(frame) = self.decoder.push(byte) else {
+                continue;
+            };
+
+            match Message::decode(&frame) {
+                Some(message) if message.sender() == self.role => {}
+                Some(message) => {
+                    // The state of every key stands on its own, whatever was received before.
+                    if matches!(message, Message::Keys(_)) {
+                        self.resync();
+                    }
+                    if self.is_new(frame.seq) {
+                        on_message(message);
+                    }
+                }
+                None => {
+                    warn!("Unknown message of kind {} from the other half", frame.kind)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::interface::usb::Leds;
+
+use super::{
+    Role,
+    frame::{Frame, MAX_PAYLOAD},
+};
+
+const KEY: u8 = 0x01;
+const KEYS: u8 = 0x02;
+const LAYERS: u8 = 0x10;
+const LEDS: u8 = 0x11;
+const USER: u8 = 0x12;
+
+/// Message between the halves of a split keyboard.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Message<'a> {
+    /// A key of the secondary half changed.
+    Key { x: u8, y: u8, pressed: bool },
+    /// State of every key of the secondary half, one bit per key row by row, least significant
+    /// bit first.
+    Keys(&'a [u8]),
+    /// Active layers of the primary half.
+    Layers(u32),
+    /// LEDs set by the host.
+    Leds(Leds),
+    /// Payload of the application.
+    User(&'a [u8]),
+}
+
+impl<'a> Message<'a> {
+    /// Half that sends the message.
+    pub fn sender(&self) -> Role {
+        match self {
+            Message::Key { .. } | Message::Keys(_) => Role::Secondary,
+            Message::Layers(_) | Message::Leds(_) | Message::User(_) => Role::Primary,
+        }
+    }
+
+    /// Kind and payload of the message, using `buf` for the payload if needed.
+    pub fn encode(&self, buf: &'a mut [u8; MAX_PAYLOAD]) -> (u8, &'a [u8]) {
+        match *self {
+            Message::Key { x, y, pressed } => {
+                buf[..3].copy_from_slice(&[x, y, pressed as u8]);
+                (KEY, &buf[..3])
+            }
+            Message::Keys(keys) => (KEYS, keys),
+            Message::Layers(layers) => {
+                buf[..4].copy_from_slice(&layers.to_le_bytes());
+                (LAYERS, &buf[..4])
+            }
+            Message::Leds(leds) => {
+                buf[0] = leds.bits();
+                (LEDS, &buf[..1])
+            }
+            Message::User(payload) => (USER, payload),
+        }
+    }
+
+    pub fn decode(frame: &'a Frame) -> Option<Self> {
+        match (frame.kind, frame.payload()) {
+            (KEY, &[x, y, pressed]) => Some(Message::Key {
+                x,
+                y,
+                pressed: pressed != 0,
+            }),
+            (KEYS, keys) => Some(Message::Keys(keys)),
+            (LAYERS, &[a, b, c, d]) => Some(Message::Layers(u32::from_le_bytes([a, b, c, d]))),
+            (LEDS, &[leds]) => Some(Message::Leds(Leds::from_bits_truncate(leds))),
+            (USER, payload) => Some(Message::User(payload)),
+            _ => None,
+        }
+    }
+}
+
+/// Packs the state of every key into `buf`, returning the number of bytes used.
+pub fn pack<const W: usize, const H: usize>(
+    keys: &[[bool; W]; H],
+    buf: &mut [u8; MAX_PAYLOAD],
+) -> usize {
+    *buf = [0; MAX_PAYLOAD];
+    for (i, &pressed) in keys.iter().flatten().enumerate() {
+        buf[i / 8] |= (pressed as u8) << (i % 8);
+    }
+    (W * H).div_ceil(8)
+}
+
+/// Unpacks the state of every key from `bytes`, or `None` if it does not match the matrix.
+pub fn unpack<const W: usize, const H: usize>(bytes: &[u8]) -> Option<[[bool; W]; H]> {
+    if bytes.len() != (W * H).div_ceil(8) {
+        return None;
+    }
+
+    let mut keys = [[false; W]; H];
+    for (i, pressed) in keys.iter_mut().flatten().enumerate() {
+        *pressed = bytes[i / 8] & 1 << (i % 8) != 0;
+    }
+    Some(keys)
+}
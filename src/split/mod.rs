@@ -0,0 +1,289 @@
+//! Split keyboards, whose halves are connected by a serial link.
+//!
+//! The secondary half runs a [`Secondary`], which scans its keys and sends them to the primary
+//! half. The primary half, connected to the host, reads them with a [`Remote`] scanner.
+//!
+//! The primary half can send its state back, so the secondary half can show it: the active
+//! layers, the LEDs set by the host, and a payload of the application. Both halves share it
+//! through a [`SplitState`], and only what changes is sent.
+//!
+//! To run the same firmware on both halves, scan with a [`Half`] instead, which takes either role
+//! as told by a [`DetectRole`], such as whether the host configured the USB device of the half.
+//!
+//! Halves wired as mirror images of each other are put back in layout order with a
+//! [`Transform`](crate::prelude::Transform) mirroring the columns of the right half.
+
+mod frame;
+mod half;
+mod link;
+mod message;
+mod remote;
+mod role;
+mod secondary;
+mod sync;
+
+#[cfg(test)]
+mod pipe;
+
+pub use half::Half;
+pub use remote::Remote;
+pub use role::{DetectRole, Handedness, Role, UsbConfigured, Vbus};
+pub use secondary::Secondary;
+pub use sync::{MAX_USER_PAYLOAD, SplitState, SyncState};
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use embassy_futures::{block_on, poll_once};
+    use embassy_time::Duration;
+
+    use crate::{
+        interface::usb::Leds,
+        scan::{
+            Composite, FULL_TRAVEL, Scan, ScanError,
+            mock::{MockMatrix, scan},
+        },
+    };
+
+    use super::{
+        pipe::{End, Pipe},
+        *,
+    };
+
+    /// Role detector whose role is set by the test.
+    struct Detect<'a>(&'a Cell<Role>);
+
+    impl DetectRole for Detect<'_> {
+        fn role(&mut self) -> Result<Role, ScanError> {
+            Ok(self.0.get())
+        }
+    }
+
+    #[test]
+    fn primary_merges_remote_keys() {
+        let (unused, wire) = (Pipe::new(), Pipe::new());
+        let local = Cell::new([[true, false]]);
+        let remote = Cell::new([[false, false]]);
+
+        let mut secondary = Secondary::new(MockMatrix::new(&remote), End::new(&unused, &wire));
+        let mut primary = Composite::<4, 1>::new()
+            .with(MockMatrix::new(&local), 0, 0)
+            .with(Remote::<_, 2, 1>::new(End::new(&wire, &unused)), 2, 0);
+
+        block_on(secondary.poll()).unwrap();
+        assert_eq!(scan(&mut primary), [[true, false, false, false]]);
+
+        remote.set([[false, true]]);
+        block_on(secondary.poll()).unwrap();
+        assert_eq!(scan(&mut primary), [[true, false, false, true]]);
+    }
+
+    #[test]
+    fn resyncs_after_noise() {
+        let (unused, wire) = (Pipe::new(), Pipe::new());
+        let keys = Cell::new([[false; 3]; 2]);
+        let mut secondary = Secondary::new(MockMatrix::new(&keys), End::new(&unused, &wire))
+            .sync_interval(Duration::from_secs(60));
+        let mut remote = Remote::<_, 3, 2>::new(End::new(&wire, &unused));
+
+        block_on(secondary.poll()).unwrap();
+        scan(&mut remote);
+
+        // The first key change is lost to noise, the second gets through.
+        keys.set([[false, true, false], [false; 3]]);
+        block_on(secondary.poll()).unwrap();
+        keys.set([[false, true, false], [true, false, false]]);
+        block_on(secondary.poll()).unwrap();
+        wire.corrupt(3);
+        assert_eq!(scan(&mut remote), [[false; 3], [true, false, false]]);
+
+        // The regular sync restores the lost key.
+        keys.set([[false, true, true], [true, false, false]]);
+        secondary = secondary.sync_interval(Duration::MIN);
+        block_on(secondary.poll()).unwrap();
+        assert_eq!(
+            scan(&mut remote),
+            [[false, true, true], [true, false, false]]
+        );
+    }
+
+    #[test]
+    fn releases_keys_when_link_is_lost() {
+        let (unused, wire) = (Pipe::new(), Pipe::new());
+        let keys = Cell::new([[true]]);
+        let mut secondary = Secondary::new(MockMatrix::new(&keys), End::new(&unused, &wire));
+        let mut remote =
+            Remote::<_, 1, 1>::new(End::new(&wire, &unused)).timeout(Duration::from_millis(1));
+
+        block_on(secondary.poll()).unwrap();
+        assert_eq!(scan(&mut remote), [[true]]);
+
+        embassy_time::block_for(Duration::from_millis(2));
+        assert_eq!(scan(&mut remote), [[false]]);
+    }
+
+    #[test]
+    fn follows_secondary_restarting_mid_stream() {
+        let (unused, wire) = (Pipe::new(), Pipe::new());
+        let keys = Cell::new([[false; 2]]);
+        let mut remote = Remote::<_, 2, 1>::new(End::new(&wire, &unused));
+
+        let mut secondary = Secondary::new(MockMatrix::new(&keys), End::new(&unused, &wire));
+        for pressed in [true, false, true] {
+            keys.set([[pressed, false]]);
+            block_on(secondary.poll()).unwrap();
+        }
+        assert_eq!(scan(&mut remote), [[true, false]]);
+
+        // The secondary half resets, starting its sequence numbers over.
+        let mut secondary = Secondary::new(MockMatrix::new(&keys), End::new(&unused, &wire))
+            .sync_interval(Duration::from_secs(60));
+        keys.set([[false, true]]);
+        block_on(secondary.poll()).unwrap();
+        assert_eq!(scan(&mut remote), [[false, true]]);
+
+        keys.set([[true, true]]);
+        block_on(secondary.poll()).unwrap();
+        assert_eq!(scan(&mut remote), [[true, true]]);
+    }
+
+    #[test]
+    fn secondary_mirrors_primary_state() {
+        static PRIMARY: SplitState = SplitState::new();
+        static SECONDARY: SplitState = SplitState::new();
+
+        let (to_primary, to_secondary) = (Pipe::new(), Pipe::new());
+        let keys = Cell::new([[false]]);
+        let mut secondary =
+            Secondary::new(MockMatrix::new(&keys), End::new(&to_secondary, &to_primary))
+                .sync(&SECONDARY);
+        let mut remote =
+            Remote::<_, 1, 1>::new(End::new(&to_primary, &to_secondary)).sync(&PRIMARY);
+
+        PRIMARY.publish(0b101, Leds::CAPS_LOCK);
+        PRIMARY.set_user_payload(&[1, 2, 3]);
+
+        // The primary half only sends after hearing from the secondary half.
+        scan(&mut remote);
+        assert!(to_secondary.is_empty());
+
+        block_on(secondary.poll()).unwrap();
+        scan(&mut remote);
+        block_on(secondary.poll()).unwrap();
+
+        let state = SECONDARY.get();
+        assert_eq!(state.layers(), 0b101);
+        assert_eq!(state.leds(), Leds::CAPS_LOCK);
+        assert_eq!(state.user_payload(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn sends_only_state_changes() {
+        static PRIMARY: SplitState = SplitState::new();
+        static SECONDARY: SplitState = SplitState::new();
+
+        let (to_primary, to_secondary) = (Pipe::new(), Pipe::new());
+        let keys = Cell::new([[false]]);
+        let mut secondary =
+            Secondary::new(MockMatrix::new(&keys), End::new(&to_secondary, &to_primary))
+                .sync_interval(Duration::MIN)
+                .sync(&SECONDARY);
+        let mut remote = Remote::<_, 1, 1>::new(End::new(&to_primary, &to_secondary))
+            .refresh_interval(Duration::from_secs(60))
+            .sync(&PRIMARY);
+
+        block_on(secondary.poll()).unwrap();
+        scan(&mut remote);
+        block_on(secondary.poll()).unwrap();
+
+        // Nothing changed, so nothing is sent.
+        scan(&mut remote);
+        assert!(to_secondary.is_empty());
+
+        PRIMARY.publish(0b10, Leds::empty());
+        block_on(secondary.poll()).unwrap();
+        scan(&mut remote);
+        assert!(!to_secondary.is_empty());
+        block_on(secondary.poll()).unwrap();
+        assert_eq!(SECONDARY.get().layers(), 0b10);
+
+        // The regular refresh sends everything again.
+        remote = remote.refresh_interval(Duration::MIN);
+        scan(&mut remote);
+        assert!(!to_secondary.is_empty());
+    }
+
+    #[test]
+    fn halves_swap_roles() {
+        let (to_left, to_right) = (Pipe::new(), Pipe::new());
+        let (left_role, right_role) = (Cell::new(Role::Primary), Cell::new(Role::Secondary));
+        let (left_keys, right_keys) = (Cell::new([[true]]), Cell::new([[false]]));
+
+        let mut left = Half::<_, _, _, 1, 2, 1>::new(
+            MockMatrix::new(&left_keys),
+            End::new(&to_left, &to_right),
+            Detect(&left_role),
+        );
+        let mut right = Half::<_, _, _, 1, 2, 1>::new(
+            MockMatrix::new(&right_keys),
+            End::new(&to_right, &to_left),
+            Detect(&right_role),
+        )
+        .handedness(Handedness::Right);
+
+        right_keys.set([[true]]);
+        assert_eq!(scan(&mut right), [[false, false]]);
+        assert_eq!(scan(&mut left), [[true, true]]);
+
+        // The right half gets connected instead, and places the left half on its left.
+        left_role.set(Role::Secondary);
+        right_role.set(Role::Primary);
+        right_keys.set([[false]]);
+        assert_eq!(scan(&mut left), [[false, false]]);
+        assert_eq!(scan(&mut right), [[true, false]]);
+    }
+
+    #[test]
+    fn primary_half_waits_for_either_half() {
+        let (to_left, to_right) = (Pipe::new(), Pipe::new());
+        let (left_keys, right_keys) = (Cell::new([[false]]), Cell::new([[false]]));
+        let role = Cell::new(Role::Primary);
+
+        let mut left = Half::<_, _, _, 1, 2, 1>::new(
+            MockMatrix::new(&left_keys).waiting(),
+            End::new(&to_left, &to_right),
+            Detect(&role),
+        );
+        let mut right = Secondary::new(MockMatrix::new(&right_keys), End::new(&to_right, &to_left))
+            .sync_interval(Duration::from_secs(60));
+
+        block_on(right.poll()).unwrap();
+        scan(&mut left);
+        assert!(poll_once(left.wait_for_press()).is_pending());
+
+        // Whatever woke the primary half is still received by the next scan.
+        right_keys.set([[true]]);
+        block_on(right.poll()).unwrap();
+        assert!(block_on(left.wait_for_press()));
+        assert_eq!(scan(&mut left), [[false, true]]);
+    }
+
+    #[test]
+    fn primary_half_reads_local_travel() {
+        let (to_left, to_right) = (Pipe::new(), Pipe::new());
+        let keys = Cell::new([[true]]);
+        let role = Cell::new(Role::Primary);
+        let mut right = Half::<_, _, _, 1, 2, 1>::new(
+            MockMatrix::new(&keys).analog(),
+            End::new(&to_right, &to_left),
+            Detect(&role),
+        )
+        .handedness(Handedness::Right);
+
+        assert_eq!(right.travel(1, 0), None);
+        scan(&mut right);
+        assert_eq!(right.travel(0, 0), None);
+        assert_eq!(right.travel(1, 0), Some(FULL_TRAVEL));
+    }
+}
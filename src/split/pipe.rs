@@ -0,0 +1,81 @@
+//! In-memory serial link for testing split keyboards on the host.
+
+extern crate std;
+
+use core::{cell::RefCell, convert::Infallible, future::poll_fn, task::Poll};
+use std::collections::VecDeque;
+
+use embedded_io_async::{ErrorType, Read, ReadReady, Write};
+
+/// Bytes sent one way, waiting to be received.
+pub struct Pipe {
+    bytes: RefCell<VecDeque<u8>>,
+}
+
+impl Pipe {
+    pub fn new() -> Self {
+        Self {
+            bytes: RefCell::new(VecDeque::new()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.borrow().is_empty()
+    }
+
+    /// Flips the bits of the byte at `index`, as noise on the line would.
+    pub fn corrupt(&self, index: usize) {
+        self.bytes.borrow_mut()[index] ^= 0xFF;
+    }
+}
+
+/// End of a link receiving from one pipe and sending into another.
+pub struct End<'a> {
+    rx: &'a Pipe,
+    tx: &'a Pipe,
+}
+
+impl<'a> End<'a> {
+    pub fn new(rx: &'a Pipe, tx: &'a Pipe) -> Self {
+        Self { rx, tx }
+    }
+}
+
+impl ErrorType for End<'_> {
+    type Error = Infallible;
+}
+
+impl Read for End<'_> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+        // Like a UART, waits for at least one byte.
+        let rx = self.rx;
+        poll_fn(|_| {
+            if rx.is_empty() {
+                Poll::Pending
+            } else {
+                Poll::Ready(())
+            }
+        })
+        .await;
+
+        let mut bytes = self.rx.bytes.borrow_mut();
+        let len = buf.len().min(bytes.len());
+        for (dst, src) in buf.iter_mut().zip(bytes.drain(..len)) {
+            *dst = src;
+        }
+        Ok(len)
+    }
+}
+
+impl ReadReady for End<'_> {
+    fn read_ready(&mut self) -> Result<bool, Infallible> {
+        Ok(!self.rx.bytes.borrow().is_empty())
+    }
+}
+
+impl Write for End<'_> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+        self.tx.bytes.borrow_mut().extend(buf);
+        Ok(buf.len())
+    }
+}
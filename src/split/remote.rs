@@ -0,0 +1,179 @@
+use embassy_time::{Duration, Instant};
+use embedded_io_async::{Read, ReadReady, Write};
+
+use crate::{
+    scan::{Scan, ScanError},
+    warn,
+};
+
+use super::{
+    Role,
+    frame::MAX_PAYLOAD,
+    link::Link,
+    message::{Message, unpack},
+    sync::{SplitState, SyncState},
+};
+
+pub const DEFAULT_REMOTE_TIMEOUT: Duration = Duration::from_millis(500);
+
+pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Scanner for the keys of the secondary half of a split keyboard, as received from its
+/// [`Secondary`](super::Secondary) over a serial link.
+///
+/// Place it next to the scanner of the primary half with a
+/// [`Composite`](crate::prelude::Composite) so the layout addresses the whole keyboard.
+///
+/// With a [`SplitState`], it also sends the state of the primary half whenever it changes. It
+/// only sends right after hearing from the secondary half, which then listens until its next
+/// scan, so the halves take turns on half-duplex links.
+///
+/// While idle, the keyboard waits for the secondary half to send anything.
+pub struct Remote<T, const W: usize, const H: usize> {
+    link: Link<T>,
+    keys: [[bool; W]; H],
+    /// Time without hearing from the secondary half after which its keys are released.
+    /// Default: 500 ms.
+    timeout: Duration,
+    last_received: Option<Instant>,
+    /// State sent to the secondary half. Default: none.
+    sync: Option<&'static SplitState>,
+    /// Time between sending the whole state, so the secondary half recovers from lost frames.
+    /// Default: 1 s.
+    refresh_interval: Duration,
+    sent: Option<SyncState>,
+    last_refresh: Option<Instant>,
+}
+
+impl<T, const W: usize, const H: usize> Remote<T, W, H>
+where
+    T: Read + ReadReady + Write,
+{
+    const _ASSERT: () = assert!(W * H <= MAX_PAYLOAD * 8 && W <= 256 && H <= 256);
+
+    pub const fn new(transport: T) -> Self {
+        let () = Self::_ASSERT;
+        Self {
+            link: Link::new(transport, Role::Primary),
+            keys: [[false; W]; H],
+            timeout: DEFAULT_REMOTE_TIMEOUT,
+            last_received: None,
+            sync: None,
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+            sent: None,
+            last_refresh: None,
+        }
+    }
+
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub const fn sync(mut self, state: &'static SplitState) -> Self {
+        self.sync = Some(state);
+        self
+    }
+
+    pub const fn refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    pub(super) fn into_transport(self) -> T {
+        self.link.into_transport()
+    }
+
+    /// Sends whatever changed in the state since it was last sent, or all of it when due.
+    async fn send_state(&mut self) -> Result<(), T::Error> {
+        let Some(sync) = self.sync else {
+            return Ok(());
+        };
+
+        let state = sync.local();
+        let sent = if self
+            .last_refresh
+            .is_none_or(|last| last.elapsed() >= self.refresh_interval)
+        {
+            self.last_refresh = Some(Instant::now());
+            None
+        } else {
+            self.sent
+        };
+
+        if sent.is_none_or(|sent| sent.layers() != state.layers()) {
+            self.link.send(Message::Layers(state.layers())).await?;
+        }
+        if sent.is_none_or(|sent| sent.leds() != state.leds()) {
+            self.link.send(Message::Leds(state.leds())).await?;
+        }
+        if sent.is_none_or(|sent| sent.user_payload() != state.user_payload()) {
+            self.link.send(Message::User(state.user_payload())).await?;
+        }
+
+        self.sent = Some(state);
+        Ok(())
+    }
+}
+
+impl<T, const W: usize, const H: usize> Scan<W, H> for Remote<T, W, H>
+where
+    T: Read + ReadReady + Write,
+{
+    type Error = ScanError;
+
+    async fn scan(&mut self, buf: &mut [[bool; W]; H]) -> Result<(), ScanError> {
+        let keys = &mut self.keys;
+        let mut received = false;
+
+        self.link
+            .receive(|message| {
+                match message {
+                    Message::Key { x, y, pressed } => {
+                        if let Some(key) = keys
+                            .get_mut(y as usize)
+                            .and_then(|row| row.get_mut(x as usize))
+                        {
+                            *key = pressed;
+                        } else {
+                            warn!("Key ({}, {}) of the other half is out of bounds", x, y);
+                        }
+                    }
+                    Message::Keys(bytes) => {
+                        if let Some(state) = unpack(bytes) {
+                            *keys = state;
+                        } else {
+                            warn!("Key states of the other half do not match the matrix");
+                        }
+                    }
+                    _ => {}
+                }
+                received = true;
+            })
+            .await
+            .map_err(ScanError::io)?;
+
+        if received {
+            self.last_received = Some(Instant::now());
+            self.send_state().await.map_err(ScanError::io)?;
+        } else if self
+            .last_received
+            .is_some_and(|last| last.elapsed() >= self.timeout)
+        {
+            warn!("Lost the other half, releasing its keys");
+            self.keys = [[false; W]; H];
+            self.last_received = None;
+            // The other half may have restarted, with its sequence numbers.
+            self.link.resync();
+            // Whatever was sent may not have been received.
+            self.sent = None;
+        }
+
+        *buf = self.keys;
+        Ok(())
+    }
+
+    async fn wait_for_press(&mut self) -> bool {
+        self.link.wait().await.is_ok()
+    }
+}
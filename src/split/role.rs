@@ -0,0 +1,125 @@
+use embedded_hal::digital::InputPin;
+
+use crate::{interface::usb, scan::ScanError};
+
+/// Role of a half of a split keyboard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Role {
+    /// The half connected to the host.
+    Primary,
+    /// The half sending its keys to the primary one.
+    Secondary,
+}
+
+/// Tells which role a half of a split keyboard takes, so the same firmware runs on both.
+pub trait DetectRole {
+    fn role(&mut self) -> Result<Role, ScanError>;
+}
+
+/// Always the same role, for firmwares built for one half.
+impl DetectRole for Role {
+    fn role(&mut self) -> Result<Role, ScanError> {
+        Ok(*self)
+    }
+}
+
+/// Primary while the host has configured the [`UsbInterface`](crate::prelude::UsbInterface) of
+/// the keyboard, secondary otherwise.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UsbConfigured;
+
+impl DetectRole for UsbConfigured {
+    fn role(&mut self) -> Result<Role, ScanError> {
+        Ok(if usb::is_configured() {
+            Role::Primary
+        } else {
+            Role::Secondary
+        })
+    }
+}
+
+/// Primary while the pin sensing VBUS is high, so the half is powered over USB, secondary
+/// otherwise.
+///
+/// Unlike [`UsbConfigured`], it knows the role as soon as the half is plugged in, before the host
+/// configures it.
+pub struct Vbus<P> {
+    pin: P,
+}
+
+impl<P: InputPin> Vbus<P> {
+    pub const fn new(pin: P) -> Self {
+        Self { pin }
+    }
+}
+
+impl<P: InputPin> DetectRole for Vbus<P> {
+    fn role(&mut self) -> Result<Role, ScanError> {
+        Ok(if self.pin.is_high().map_err(ScanError::pin)? {
+            Role::Primary
+        } else {
+            Role::Secondary
+        })
+    }
+}
+
+/// Which half of a split keyboard this is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Handedness {
+    /// Default.
+    #[default]
+    Left,
+    Right,
+}
+
+impl Handedness {
+    /// Reads the handedness from a pin tied high on one half and low on the other, with `high`
+    /// being the handedness of the half where it is high.
+    pub fn from_pin<P: InputPin>(pin: &mut P, high: Handedness) -> Result<Self, ScanError> {
+        Ok(if pin.is_high().map_err(ScanError::pin)? {
+            high
+        } else {
+            high.other()
+        })
+    }
+
+    /// Decodes the handedness stored with [`flag`](Self::flag), or `None` if nothing valid is
+    /// stored, such as erased flash.
+    pub const fn from_flag(flag: u8) -> Option<Self> {
+        match flag {
+            0 => Some(Handedness::Left),
+            1 => Some(Handedness::Right),
+            _ => None,
+        }
+    }
+
+    /// Encodes the handedness to be stored, for example in flash or EEPROM.
+    pub const fn flag(self) -> u8 {
+        match self {
+            Handedness::Left => 0,
+            Handedness::Right => 1,
+        }
+    }
+
+    pub const fn other(self) -> Self {
+        match self {
+            Handedness::Left => Handedness::Right,
+            Handedness::Right => Handedness::Left,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stored_flag_round_trips() {
+        for handedness in [Handedness::Left, Handedness::Right] {
+            assert_eq!(Handedness::from_flag(handedness.flag()), Some(handedness));
+        }
+        assert_eq!(Handedness::from_flag(0xFF), None);
+    }
+}
@@ -0,0 +1,132 @@
+use embassy_time::{Duration, Instant, Ticker};
+use embedded_io_async::{Read, ReadReady, Write};
+
+use crate::{SCAN_INTERVAL, debug, error, scan::Scan};
+
+use super::{
+    Role,
+    frame::MAX_PAYLOAD,
+    link::Link,
+    message::{Message, pack},
+    sync::SplitState,
+};
+
+pub const DEFAULT_SYNC_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Secondary half of a split keyboard, scanning its keys and sending them to the primary half,
+/// which reads them with a [`Remote`](super::Remote).
+///
+/// Every key that changes is sent as soon as it is scanned. The state of every key is also sent
+/// regularly, so the primary half recovers from lost frames and can tell the link is alive.
+///
+/// With a [`SplitState`], it also keeps it up to date with the state the primary half sends, for
+/// indicators and displays to show. The primary half only sends right after hearing from the
+/// secondary half, so half-duplex single-wire links work as well as full-duplex ones.
+pub struct Secondary<S, T, const W: usize, const H: usize> {
+    scanner: S,
+    link: Link<T>,
+    keys: [[bool; W]; H],
+    /// Time between sending the state of every key. Default: 100 ms.
+    sync_interval: Duration,
+    last_sync: Option<Instant>,
+    /// State received from the primary half. Default: none.
+    sync: Option<&'static SplitState>,
+}
+
+impl<S, T, const W: usize, const H: usize> Secondary<S, T, W, H>
+where
+    S: Scan<W, H>,
+    T: Read + ReadReady + Write,
+{
+    const _ASSERT: () = assert!(W * H <= MAX_PAYLOAD * 8 && W <= 256 && H <= 256);
+
+    pub const fn new(scanner: S, transport: T) -> Self {
+        let () = Self::_ASSERT;
+        Self {
+            scanner,
+            link: Link::new(transport, Role::Secondary),
+            keys: [[false; W]; H],
+            sync_interval: DEFAULT_SYNC_INTERVAL,
+            last_sync: None,
+            sync: None,
+        }
+    }
+
+    pub const fn sync_interval(mut self, interval: Duration) -> Self {
+        self.sync_interval = interval;
+        self
+    }
+
+    pub const fn sync(mut self, state: &'static SplitState) -> Self {
+        self.sync = Some(state);
+        self
+    }
+
+    pub(super) fn into_parts(self) -> (S, T) {
+        (self.scanner, self.link.into_transport())
+    }
+
+    pub async fn run(mut self) -> ! {
+        debug!("Running secondary half main loop...");
+        let mut ticker = Ticker::every(SCAN_INTERVAL);
+
+        loop {
+            if let Err(_e) = self.poll().await {
+                error!(
+                    "Failed to talk to the primary half: {}",
+                    defmt::Debug2Format(&_e)
+                );
+            }
+
+            ticker.next().await;
+        }
+    }
+
+    /// Receives the state of the primary half, then scans the keys and sends whatever the primary
+    /// half needs to know.
+    pub(super) async fn poll(&mut self) -> Result<(), T::Error> {
+        let sync = self.sync;
+        self.link
+            .receive(|message| {
+                let Some(sync) = sync else {
+                    return;
+                };
+
+                sync.receive(|state| match message {
+                    Message::Layers(layers) => state.set_layers(layers),
+                    Message::Leds(leds) => state.set_leds(leds),
+                    Message::User(payload) => state.set_user_payload(payload),
+                    _ => {}
+                });
+            })
+            .await?;
+
+        let mut scan = [[false; W]; H];
+        if let Err(_e) = self.scanner.scan(&mut scan).await {
+            error!("Failed to scan matrix: {}", defmt::Debug2Format(&_e));
+            return Ok(());
+        }
+
+        for (y, row) in scan.iter().enumerate() {
+            for (x, &pressed) in row.iter().enumerate() {
+                if pressed != self.keys[y][x] {
+                    let (x, y) = (x as u8, y as u8);
+                    self.link.send(Message::Key { x, y, pressed }).await?;
+                }
+            }
+        }
+        self.keys = scan;
+
+        if self
+            .last_sync
+            .is_none_or(|last| last.elapsed() >= self.sync_interval)
+        {
+            let mut buf = [0; MAX_PAYLOAD];
+            let len = pack(&self.keys, &mut buf);
+            self.link.send(Message::Keys(&buf[..len])).await?;
+            self.last_sync = Some(Instant::now());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use core::cell::Cell;
+
+use critical_section::Mutex;
+
+use crate::interface::usb::Leds;
+
+use super::{Role, frame::MAX_PAYLOAD};
+
+/// Longest user payload.
+pub const MAX_USER_PAYLOAD: usize = MAX_PAYLOAD;
+
+/// State of the primary half mirrored on the secondary half, for its indicators and displays.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SyncState {
+    layers: u32,
+    leds: Leds,
+    user: [u8; MAX_USER_PAYLOAD],
+    user_len: usize,
+}
+
+impl SyncState {
+    const fn new() -> Self {
+        Self {
+            layers: 0,
+            leds: Leds::empty(),
+            user: [0; MAX_USER_PAYLOAD],
+            user_len: 0,
+        }
+    }
+
+    /// Active layers, one bit per layer.
+    pub fn layers(&self) -> u32 {
+        self.layers
+    }
+
+    /// LEDs as set by the host.
+    pub fn leds(&self) -> Leds {
+        self.leds
+    }
+
+    /// Payload of the application, such as RGB or backlight settings.
+    pub fn user_payload(&self) -> &[u8] {
+        &self.user[..self.user_len]
+    }
+
+    pub(super) fn set_layers(&mut self, layers: u32) {
+        self.layers = layers;
+    }
+
+    pub(super) fn set_leds(&mut self, leds: Leds) {
+        self.leds = leds;
+    }
+
+    pub(super) fn set_user_payload(&mut self, payload: &[u8]) {
+        self.user[..payload.len()].copy_from_slice(payload);
+        self.user_len = payload.len();
+    }
+}
+
+/// State shared between the keyboard and the split link.
+///
+/// On the primary half, give it to the [`Keyboard`](crate::Keyboard), which keeps the layers and
+/// LEDs up to date, and to the [`Remote`](super::Remote), which sends whatever changes. On the
+/// secondary half, give it to the [`Secondary`](super::Secondary), which keeps it up to date with
+/// what the primary half sends.
+///
+/// Either way, [`get`](Self::get) returns the state of the primary half. Giving it to the keyboard
+/// on both halves is fine, so a single firmware works for both.
+#[derive(Debug)]
+pub struct SplitState {
+    state: Mutex<Cell<Shared>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Shared {
+    /// State of this half, sent when it is the primary one.
+    local: SyncState,
+    /// State received from the primary half.
+    received: SyncState,
+    role: Role,
+}
+
+impl SplitState {
+    pub const fn new() -> Self {
+        Self {
+            state: Mutex::new(Cell::new(Shared {
+                local: SyncState::new(),
+                received: SyncState::new(),
+                role: Role::Primary,
+            })),
+        }
+    }
+
+    pub fn get(&self) -> SyncState {
+        self.with(|shared| match shared.role {
+            Role::Primary => shared.local,
+            Role::Secondary => shared.received,
+        })
+    }
+
+    /// Sets the payload sent to the secondary half, of at most [`MAX_USER_PAYLOAD`] bytes.
+    pub fn set_user_payload(&self, payload: &[u8]) {
+        assert!(payload.len() <= MAX_USER_PAYLOAD);
+        self.with(|shared| shared.local.set_user_payload(payload));
+    }
+
+    /// Sets the state kept by the keyboard.
+    pub(crate) fn publish(&self, layers: u32, leds: Leds) {
+        self.with(|shared| {
+            shared.local.set_layers(layers);
+            shared.local.set_leds(leds);
+        });
+    }
+
+    /// State of this half, to be sent as the primary one.
+    pub(super) fn local(&self) -> SyncState {
+        self.with(|shared| {
+            shared.role = Role::Primary;
+            shared.local
+        })
+    }
+
+    /// Updates the state received as the secondary half.
+    pub(super) fn receive(&self, f: impl FnOnce(&mut SyncState)) {
+        self.with(|shared| {
+            shared.role = Role::Secondary;
+            f(&mut shared.received);
+        })
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&mut Shared) -> R) -> R {
+        critical_section::with(|cs| {
+            let cell = self.state.borrow(cs);
+            let mut shared = cell.get();
+            let result = f(&mut shared);
+            cell.set(shared);
+            result
+        })
+    }
+}
+
+impl Default for SplitState {
+    fn default() -> Self {
+        Self::new()
+    }
+}